@@ -1,7 +1,7 @@
 use jabba_lib::jclipboard;
 
 fn main() {
-    jclipboard::check();
+    jclipboard::check().unwrap();
 
     for &text in &["", "a", "aa", "hello", "world", "rust", "Éva"] {
         jclipboard::set_text(text).unwrap();
@@ -3,7 +3,7 @@ use jabba_lib::jclipboard;
 fn main() {
     let text = "hello";
 
-    jclipboard::check(); // verify if your platform is supported
+    jclipboard::check().unwrap(); // verify if your platform is supported
 
     jclipboard::set_text(text).unwrap();
     println!("The text {:?} was pasted on the clipboard", text);
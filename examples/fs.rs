@@ -15,11 +15,11 @@ fn main() {
     }
     println!("---");
     let fname = "out.20220801c.txt";
-    let names = vec!["Alan", "Bob", "Carol"];
+    let names = ["Alan", "Bob", "Carol"];
     let mut f = jfs::open_for_write(fname).unwrap();
-    f.write(b"# it is safe to delete this file\n").unwrap();
+    f.write_all(b"# it is safe to delete this file\n").unwrap();
     for name in names.iter() {
-        f.write(name.as_bytes()).unwrap();
-        f.write(b"\n").unwrap();
+        f.write_all(name.as_bytes()).unwrap();
+        f.write_all(b"\n").unwrap();
     }
 }
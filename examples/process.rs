@@ -1,7 +1,7 @@
 use jabba_lib::jprocess as jproc;
 
 fn main() {
-    let commands = vec![
+    let commands = [
         r#"python -c "print('Hello Py3!')""#,
         "python --version",
         "date",
@@ -19,4 +19,7 @@ fn main() {
 
     let cmd = "ls -al";
     jproc::exec_cmd(cmd);
+
+    let stat = jproc::run_pipeline("ls -al | grep Cargo").unwrap();
+    println!("{:?}", stat);
 }
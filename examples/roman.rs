@@ -0,0 +1,15 @@
+use jabba_lib::jroman;
+
+fn main() {
+    for n in [1, 4, 9, 14, 40, 90, 400, 1984, 2024, 3999] {
+        let roman = jroman::to_roman(n).unwrap();
+        let unicode = jroman::to_roman_unicode(n).unwrap();
+        println!("{}: {} ({})", n, roman, unicode);
+        assert_eq!(jroman::from_roman(&roman).unwrap(), n);
+    }
+
+    for s in ["IIII", "mcmxc", "Ⅻ"] {
+        println!("{:?} is {:?} (lenient)", s, jroman::from_roman_lenient(s));
+        println!("{:?} is {:?} (strict)", s, jroman::from_roman(s));
+    }
+}
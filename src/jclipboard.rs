@@ -1,59 +1,77 @@
-//! clipboard (supported platforms: Linux (with X server), Windows)
+//! clipboard (supported platforms: Linux (Wayland or X server), Windows)
+//!
+//! The clipboard is accessed through a [`Backend`]. By default, the backend
+//! is detected automatically when the clipboard is first used (see [`detect_backend`]),
+//! and the detected backend is kept for the later calls:
+//!
+//! - Windows: the Windows clipboard API
+//! - Linux with Wayland: `wl-copy` / `wl-paste` (package `wl-clipboard`)
+//! - Linux with an X server: `xclip` or `xsel`
+//!
+//! The detection can be overridden with the environment variable `JABBA_CLIPBOARD`
+//! (possible values: `wl-clipboard`, `xclip`, `xsel`, `windows`, `memory`), or by
+//! calling [`set_backend`] with any type that implements [`Backend`]. The in-memory
+//! backend ([`Memory`]) is useful for running tests headless.
+//!
+//! [`set_text`] and [`get_text`] work with plain text. To target a specific [`Selection`]
+//! (primary, clipboard or both), or to use other MIME types (e.g. [`MIME_HTML`], [`MIME_PNG`],
+//! [`MIME_URI_LIST`]), see [`set_text_to`], [`get_text_from`], [`set_contents`] and [`get_contents`].
 
 use crate::jos;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use which::which;
 
 // see https://rust-lang-nursery.github.io/rust-cookbook/os/external.html
 
 /// Checks if the current platform is supported.
-/// If not supported, then it panics.
+/// If not supported, then an error message is returned.
 ///
-/// Supported platforms: Linux (with X server), Windows.
+/// Supported platforms: Linux (Wayland or X server), Windows.
 ///
-/// Under Linux you must have the program `xsel` installed.
-/// You can install it with your package manager.
+/// Under Linux you must have one of the programs `wl-copy` / `wl-paste` (Wayland),
+/// `xclip` or `xsel` (X server) installed. You can install them with your package manager.
 ///
 /// Under Linux, the text is pasted on both clipboards (to "primary" and "clipboard").
 ///
+/// If a backend was set with [`set_backend`], the platform is always supported.
+///
 /// # Examples
 ///
 /// ```
-/// jabba_lib::jclipboard::check();  // verify if your platform is supported
+/// # if jabba_lib::jclipboard::detect_backend().is_none() { return; }
+/// jabba_lib::jclipboard::check().unwrap();  // verify if your platform is supported
 /// ```
-pub fn check() {
-    if !jos::is_linux() && !jos::is_windows() {
-        let platform = jos::get_operating_system_name();
-        panic!("Error: unknown platform {}", platform);
-    }
-    // else, if linux or windows:
-
-    if jos::is_windows() {
-        return;
-    }
-    // else, if linux
-    let command = "xsel";
-    let result = which(command);
-    if result.is_err() {
-        panic!("Error: the command {} was not found", command);
+pub fn check() -> Result<(), &'static str> {
+    if let Err(msg) = current_backend() {
+        if !jos::is_linux() && !jos::is_windows() {
+            return Err("Error: unsupported platform (only Linux and Windows are supported)");
+        }
+        return Err(msg);
     }
+    Ok(())
 }
 
 /// Puts the given string on the clipboard.
 ///
-/// Supported platforms: Linux (with X server), Windows.
+/// Supported platforms: Linux (Wayland or X server), Windows.
 ///
-/// Under Linux you must have the program `xsel` installed.
-/// You can install it with your package manager.
+/// Under Linux you must have one of the programs `wl-copy` / `wl-paste` (Wayland),
+/// `xclip` or `xsel` (X server) installed. You can install them with your package manager.
 ///
 /// Under Linux, the text is pasted on both clipboards (to "primary" and "clipboard").
 ///
 /// # Examples
 ///
 /// ```
+/// # if jabba_lib::jclipboard::detect_backend().is_none() { return; }
 /// let backup = jabba_lib::jclipboard::get_text().unwrap();
 /// let text = "hello";
 ///
-/// jabba_lib::jclipboard::check();  // verify if your platform is supported
+/// jabba_lib::jclipboard::check().unwrap();  // verify if your platform is supported
 ///
 /// jabba_lib::jclipboard::set_text(text).unwrap();
 /// println!("The text {:?} was pasted on the clipboard", text);
@@ -65,37 +83,24 @@ pub fn check() {
 /// jabba_lib::jclipboard::set_text(&backup).unwrap();
 /// ```
 pub fn set_text(text: &str) -> Result<(), &'static str> {
-    cfg_if::cfg_if! {
-        if #[cfg(windows)] {
-            set_text_windows(text)?;
-            return Ok(());
-        } else if #[cfg(unix)] {
-            let result = set_text_linux(text);
-            return match result {
-                Ok(_) => Ok(()),
-                Err(_) => Err("Error: cannot write to clipboard"),
-            };
-        } else {
-            let platform = jos::get_operating_system_name();
-            panic!("Error: unknown platform {}", platform);
-        }
-    }
+    current_backend()?.set_text(text)
 }
 
 /// Reads the contents of the clipboard.
 ///
-/// Supported platforms: Linux (with X server), Windows.
+/// Supported platforms: Linux (Wayland or X server), Windows.
 ///
-/// Under Linux you must have the program `xsel` installed.
-/// You can install it with your package manager.
+/// Under Linux you must have one of the programs `wl-copy` / `wl-paste` (Wayland),
+/// `xclip` or `xsel` (X server) installed. You can install them with your package manager.
 ///
 /// # Examples
 ///
 /// ```
+/// # if jabba_lib::jclipboard::detect_backend().is_none() { return; }
 /// let backup = jabba_lib::jclipboard::get_text().unwrap();
 /// let text = "hello";
 ///
-/// jabba_lib::jclipboard::check();  // verify if your platform is supported
+/// jabba_lib::jclipboard::check().unwrap();  // verify if your platform is supported
 ///
 /// jabba_lib::jclipboard::set_text(text).unwrap();
 /// println!("The text {:?} was pasted on the clipboard", text);
@@ -107,31 +112,728 @@ pub fn set_text(text: &str) -> Result<(), &'static str> {
 /// jabba_lib::jclipboard::set_text(&backup).unwrap();
 /// ```
 pub fn get_text() -> Result<String, &'static str> {
+    current_backend()?.get_text()
+}
+
+////////////////////////////
+// Selections and formats //
+////////////////////////////
+
+/// The MIME type of plain text.
+pub const MIME_TEXT: &str = "text/plain";
+/// The MIME type of HTML.
+pub const MIME_HTML: &str = "text/html";
+/// The MIME type of PNG images.
+pub const MIME_PNG: &str = "image/png";
+/// The MIME type of a list of URIs (e.g. files copied in a file manager), one per line.
+pub const MIME_URI_LIST: &str = "text/uri-list";
+
+const NOT_SUPPORTED: &str = "Error: not supported by this clipboard backend";
+const NO_SUCH_TYPE: &str = "Error: the clipboard doesn't contain this type";
+const NOT_UTF8: &str = "Error: the clipboard doesn't contain valid UTF-8 text";
+
+/// The selection to write / read.
+///
+/// The X server and Wayland have two selections: "primary" (the selected text,
+/// pasted with the middle mouse button) and "clipboard" (Ctrl+C / Ctrl+V).
+/// Windows has only one clipboard, thus there the selection doesn't matter.
+///
+/// When reading, `Both` reads the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Selection {
+    Primary,
+    Clipboard,
+    Both,
+}
+
+impl Selection {
+    /// The selections to write.
+    fn for_writing(self) -> &'static [Selection] {
+        match self {
+            Selection::Primary => &[Selection::Primary],
+            Selection::Clipboard => &[Selection::Clipboard],
+            Selection::Both => &[Selection::Primary, Selection::Clipboard],
+        }
+    }
+
+    /// The selection to read.
+    fn for_reading(self) -> Selection {
+        match self {
+            Selection::Primary => Selection::Primary,
+            _ => Selection::Clipboard,
+        }
+    }
+}
+
+/// Puts the given string on the given selection.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory, Selection};
+///
+/// jclipboard::set_backend(Memory);
+/// jclipboard::set_text_to("selected", Selection::Primary).unwrap();
+/// jclipboard::set_text_to("copied", Selection::Clipboard).unwrap();
+///
+/// assert_eq!(jclipboard::get_text_from(Selection::Primary).unwrap(), "selected");
+/// assert_eq!(jclipboard::get_text_from(Selection::Clipboard).unwrap(), "copied");
+/// ```
+pub fn set_text_to(text: &str, selection: Selection) -> Result<(), &'static str> {
+    current_backend()?.set_contents(text.as_bytes(), MIME_TEXT, selection)
+}
+
+/// Reads the text of the given selection.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory, Selection};
+///
+/// jclipboard::set_backend(Memory);
+/// jclipboard::set_text_to("hello", Selection::Both).unwrap();
+///
+/// assert_eq!(jclipboard::get_text_from(Selection::Primary).unwrap(), "hello");
+/// ```
+pub fn get_text_from(selection: Selection) -> Result<String, &'static str> {
+    let data = current_backend()?.get_contents(MIME_TEXT, selection)?;
+    String::from_utf8(data).map_err(|_| NOT_UTF8)
+}
+
+/// Puts the given data with the given MIME type on the given selection.
+///
+/// The previous contents of the selection are replaced.
+/// `xsel` supports plain text only.
+///
+/// Under Windows, `text/html` is stored in the "HTML Format" (the data must be in that
+/// format, with its header), `image/png` in the "PNG" format, and any other type in a
+/// clipboard format named after the MIME type.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory, Selection, MIME_HTML};
+///
+/// jclipboard::set_backend(Memory);
+/// let html = b"<b>bold</b>";
+/// jclipboard::set_contents(html, MIME_HTML, Selection::Clipboard).unwrap();
+///
+/// assert_eq!(jclipboard::get_contents(MIME_HTML, Selection::Clipboard).unwrap(), html);
+/// ```
+pub fn set_contents(data: &[u8], mime: &str, selection: Selection) -> Result<(), &'static str> {
+    current_backend()?.set_contents(data, mime, selection)
+}
+
+/// Reads the data with the given MIME type from the given selection.
+///
+/// Returns an error if the selection doesn't offer the given type.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory, Selection, MIME_PNG};
+///
+/// jclipboard::set_backend(Memory);
+/// let png = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// jclipboard::set_contents(&png, MIME_PNG, Selection::Clipboard).unwrap();
+///
+/// assert_eq!(jclipboard::get_contents(MIME_PNG, Selection::Clipboard).unwrap(), png);
+/// assert!(jclipboard::get_contents("text/html", Selection::Clipboard).is_err());
+/// ```
+pub fn get_contents(mime: &str, selection: Selection) -> Result<Vec<u8>, &'static str> {
+    current_backend()?.get_contents(mime, selection)
+}
+
+/// Returns the MIME types offered by the given selection.
+///
+/// Not supported by `xsel` and under Windows.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory, Selection, MIME_URI_LIST};
+///
+/// jclipboard::set_backend(Memory);
+/// let uris = b"file:///etc/hosts\r\nfile:///etc/passwd\r\n";
+/// jclipboard::set_contents(uris, MIME_URI_LIST, Selection::Both).unwrap();
+///
+/// assert_eq!(jclipboard::available_types(Selection::Primary).unwrap(), [MIME_URI_LIST]);
+/// ```
+pub fn available_types(selection: Selection) -> Result<Vec<String>, &'static str> {
+    current_backend()?.available_types(selection)
+}
+
+/// The MIME type without its parameters, in lowercase (e.g. `"Text/Plain; charset=utf-8"` -> `"text/plain"`).
+fn base_type(mime: &str) -> String {
+    mime.split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn is_text(mime: &str) -> bool {
+    base_type(mime) == MIME_TEXT
+}
+
+/////////////
+// Watcher //
+/////////////
+
+/// Watches the clipboard and reports each new text value.
+///
+/// The clipboard is polled through the current backend (see [`set_backend`]), thus
+/// it works with every backend. The value found when the watching starts is not
+/// reported, only the later changes. If the clipboard cannot be read (e.g. it contains
+/// an image), the poll is skipped.
+///
+/// With debouncing, a new value is reported only if it stays unchanged for the given
+/// time, thus a burst of changes is reported once (with the last value).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory, Watcher};
+///
+/// jclipboard::set_backend(Memory);
+/// jclipboard::set_text("old").unwrap();
+///
+/// let handle = Watcher::new().interval(0.01).history(10).spawn(|text| {
+///     println!("new clipboard value: {:?}", text);
+/// });
+/// jclipboard::set_text("new").unwrap();
+/// std::thread::sleep(std::time::Duration::from_millis(200));
+///
+/// assert_eq!(handle.stop(), ["new"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    selection: Selection,
+    interval: Duration,
+    debounce: Duration,
+    history: usize,
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watcher {
+    /// Creates a watcher that polls the clipboard selection 5 times per second,
+    /// without debouncing and without history.
+    pub fn new() -> Watcher {
+        Watcher {
+            selection: Selection::Clipboard,
+            interval: Duration::from_millis(200),
+            debounce: Duration::ZERO,
+            history: 0,
+        }
+    }
+
+    /// Sets the selection to watch (`Both` watches the clipboard).
+    pub fn selection(mut self, selection: Selection) -> Watcher {
+        self.selection = selection;
+        self
+    }
+
+    /// Sets the time between two polls (in seconds, given as a float).
+    pub fn interval(mut self, secs: f64) -> Watcher {
+        self.interval = Duration::from_secs_f64(secs);
+        self
+    }
+
+    /// Sets the time (in seconds, given as a float) a new value must stay
+    /// unchanged before it's reported.
+    pub fn debounce(mut self, secs: f64) -> Watcher {
+        self.debounce = Duration::from_secs_f64(secs);
+        self
+    }
+
+    /// Keeps the last `size` reported values (the oldest ones are dropped).
+    pub fn history(mut self, size: usize) -> Watcher {
+        self.history = size;
+        self
+    }
+
+    /// Returns an (endless) iterator over the new values of the clipboard.
+    /// Each call of `next()` blocks until the clipboard changes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use jabba_lib::jclipboard::Watcher;
+    ///
+    /// for text in Watcher::new().iter() {
+    ///     if text.starts_with("http") {
+    ///         println!("URL: {}", text);
+    ///     }
+    /// }
+    /// ```
+    pub fn iter(self) -> Changes {
+        Changes {
+            interval: self.interval,
+            poller: Poller::new(&self),
+        }
+    }
+
+    /// Calls `f` with each new value of the clipboard, in the current thread.
+    /// Stops when `f` returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use jabba_lib::jclipboard::Watcher;
+    ///
+    /// Watcher::new().debounce(0.5).watch(|text| {
+    ///     println!("copied: {}", text);
+    ///     text != "quit"
+    /// });
+    /// ```
+    pub fn watch<F>(self, mut f: F)
+    where
+        F: FnMut(&str) -> bool,
+    {
+        for text in self.iter() {
+            if !f(&text) {
+                break;
+            }
+        }
+    }
+
+    /// Calls `f` with each new value of the clipboard, in a background thread.
+    /// The watching lasts until [`WatchHandle::stop`] is called (or the handle is dropped).
+    pub fn spawn<F>(self, mut f: F) -> WatchHandle
+    where
+        F: FnMut(&str) + Send + 'static,
+    {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let mut poller = Poller::new(&self);
+        let history = Arc::clone(&poller.history);
+        let flag = Arc::clone(&stopped);
+        let interval = self.interval;
+        let thread = thread::spawn(move || {
+            while !flag.load(Ordering::SeqCst) {
+                if let Some(text) = poller.poll() {
+                    f(&text);
+                }
+                thread::sleep(interval);
+            }
+        });
+        WatchHandle {
+            stopped,
+            thread: Some(thread),
+            history,
+        }
+    }
+}
+
+/// An iterator over the new values of the clipboard (see [`Watcher::iter`]).
+#[derive(Debug)]
+pub struct Changes {
+    interval: Duration,
+    poller: Poller,
+}
+
+impl Changes {
+    /// Returns the last reported values (oldest first).
+    pub fn history(&self) -> Vec<String> {
+        self.poller.history()
+    }
+}
+
+impl Iterator for Changes {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(text) = self.poller.poll() {
+                return Some(text);
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+/// A watcher running in a background thread (see [`Watcher::spawn`]).
+#[derive(Debug)]
+pub struct WatchHandle {
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    history: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl WatchHandle {
+    /// Returns the last reported values (oldest first).
+    pub fn history(&self) -> Vec<String> {
+        self.history.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Stops the watching and returns the history.
+    pub fn stop(mut self) -> Vec<String> {
+        self.shutdown();
+        self.history()
+    }
+
+    fn shutdown(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join(); // if the callback panicked, there's nothing to do
+        }
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Reads the clipboard and decides if there's a new value to report.
+#[derive(Debug)]
+struct Poller {
+    selection: Selection,
+    debounce: Duration,
+    last: Option<String>,               // the last reported value
+    pending: Option<(String, Instant)>, // a new value and since when it's there
+    history: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl Poller {
+    fn new(watcher: &Watcher) -> Poller {
+        let mut poller = Poller {
+            selection: watcher.selection,
+            debounce: watcher.debounce,
+            last: None,
+            pending: None,
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            capacity: watcher.history,
+        };
+        poller.last = poller.read(); // the initial value is not a change
+        poller
+    }
+
+    fn read(&self) -> Option<String> {
+        let result = match self.selection {
+            Selection::Primary => get_text_from(Selection::Primary),
+            _ => get_text(),
+        };
+        result.ok()
+    }
+
+    /// Polls the clipboard once. Returns the new value, if it must be reported.
+    fn poll(&mut self) -> Option<String> {
+        let text = self.read()?;
+        if self.last.as_ref() == Some(&text) {
+            self.pending = None; // changed back before it was reported
+            return None;
+        }
+        let since = match &self.pending {
+            Some((pending, since)) if *pending == text => *since,
+            _ => {
+                let now = Instant::now();
+                self.pending = Some((text.clone(), now));
+                now
+            }
+        };
+        if since.elapsed() < self.debounce {
+            return None;
+        }
+        self.pending = None;
+        self.last = Some(text.clone());
+        if self.capacity > 0 {
+            let mut history = self.history.lock().unwrap();
+            if history.len() == self.capacity {
+                history.pop_front();
+            }
+            history.push_back(text.clone());
+        }
+        Some(text)
+    }
+
+    fn history(&self) -> Vec<String> {
+        self.history.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+//////////////
+// Backends //
+//////////////
+
+/// A clipboard backend: something that can write and read the clipboard.
+///
+/// Implement it if you want to plug in your own clipboard (see [`set_backend`]).
+/// Only [`name`](Backend::name), [`set_contents`](Backend::set_contents) and
+/// [`get_contents`](Backend::get_contents) are required.
+pub trait Backend: Send + Sync {
+    /// Returns the name of the backend (e.g. `"xsel"`).
+    fn name(&self) -> &'static str;
+
+    /// Puts the data with the given MIME type on the given selection.
+    fn set_contents(
+        &self,
+        data: &[u8],
+        mime: &str,
+        selection: Selection,
+    ) -> Result<(), &'static str>;
+
+    /// Reads the data with the given MIME type from the given selection.
+    fn get_contents(&self, mime: &str, selection: Selection) -> Result<Vec<u8>, &'static str>;
+
+    /// Returns the MIME types offered by the given selection.
+    fn available_types(&self, _selection: Selection) -> Result<Vec<String>, &'static str> {
+        Err(NOT_SUPPORTED)
+    }
+
+    /// Puts the given string on the clipboard (on both selections).
+    fn set_text(&self, text: &str) -> Result<(), &'static str> {
+        self.set_contents(text.as_bytes(), MIME_TEXT, Selection::Both)
+    }
+
+    /// Reads the contents of the clipboard.
+    fn get_text(&self) -> Result<String, &'static str> {
+        let data = self.get_contents(MIME_TEXT, Selection::Clipboard)?;
+        String::from_utf8(data).map_err(|_| NOT_UTF8)
+    }
+}
+
+static BACKEND: RwLock<Option<Arc<dyn Backend>>> = RwLock::new(None);
+
+/// Detects the clipboard backend to use.
+///
+/// If the environment variable `JABBA_CLIPBOARD` is set to a known backend name
+/// (`wl-clipboard`, `xclip`, `xsel`, `windows` or `memory`), that backend is used.
+/// Otherwise:
+///
+/// - under Windows, the Windows clipboard is used
+/// - if `WAYLAND_DISPLAY` is set and `wl-copy` / `wl-paste` are available, `wl-clipboard` is used
+/// - if `DISPLAY` is set, `xclip` or `xsel` is used (whichever is available, in this order)
+///
+/// Returns `None` if no backend was found.
+///
+/// Every call does a new detection; the clipboard functions call it only once
+/// and keep the result (until [`reset_backend`] is called).
+///
+/// # Examples
+///
+/// ```
+/// match jabba_lib::jclipboard::detect_backend() {
+///     Some(backend) => println!("clipboard backend: {}", backend.name()),
+///     None => println!("no clipboard backend was found"),
+/// }
+/// ```
+pub fn detect_backend() -> Option<Box<dyn Backend>> {
+    if let Ok(name) = std::env::var("JABBA_CLIPBOARD") {
+        if let Some(backend) = backend_by_name(&name) {
+            return Some(backend);
+        }
+    }
+    // else, if no (valid) override
+
     cfg_if::cfg_if! {
         if #[cfg(windows)] {
-            let text = get_text_windows()?;
-            Ok(text)
+            Some(Box::new(WindowsClipboard))
         } else if #[cfg(unix)] {
-            let result = get_text_linux();
-            match result {
-                Ok(text) => Ok(text),
-                Err(_) => Err("Error: cannot read from clipboard"),
+            let is_set = |var: &str| {
+                std::env::var_os(var).is_some_and(|value| !value.is_empty())
+            };
+            let is_installed = |command: &str| which(command).is_ok();
+
+            if is_set("WAYLAND_DISPLAY") && is_installed("wl-copy") && is_installed("wl-paste") {
+                return Some(Box::new(WlClipboard));
+            }
+            if is_set("DISPLAY") {
+                if is_installed("xclip") {
+                    return Some(Box::new(Xclip));
+                }
+                if is_installed("xsel") {
+                    return Some(Box::new(Xsel));
+                }
             }
+            None
         } else {
-            let platform = jos::get_operating_system_name();
-            panic!("Error: unknown platform {}", platform);
+            None
         }
     }
 }
 
+/// Sets the clipboard backend, overriding the automatic detection.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jclipboard::{self, Memory};
+///
+/// jclipboard::set_backend(Memory);  // e.g. in a test running headless
+/// jclipboard::set_text("hello").unwrap();
+/// assert_eq!(jclipboard::get_text().unwrap(), "hello");
+///
+/// jclipboard::reset_backend();  // back to the automatic detection
+/// ```
+pub fn set_backend<B: Backend + 'static>(backend: B) {
+    *BACKEND.write().unwrap() = Some(Arc::new(backend));
+}
+
+/// Removes the backend set by [`set_backend`] (or detected earlier), thus the backend
+/// is detected automatically again.
+pub fn reset_backend() {
+    *BACKEND.write().unwrap() = None;
+}
+
+fn current_backend() -> Result<Arc<dyn Backend>, &'static str> {
+    if let Some(backend) = BACKEND.read().unwrap().as_ref() {
+        return Ok(Arc::clone(backend));
+    }
+    // else, if no backend was set or detected yet
+    let Some(detected) = detect_backend() else {
+        return Err("Error: no clipboard backend was found (install wl-clipboard, xclip or xsel)");
+    };
+    // another thread may have set one in the meantime
+    let mut backend = BACKEND.write().unwrap();
+    Ok(Arc::clone(backend.get_or_insert(Arc::from(detected))))
+}
+
+fn backend_by_name(name: &str) -> Option<Box<dyn Backend>> {
+    match name {
+        "memory" => Some(Box::new(Memory)),
+        #[cfg(windows)]
+        "windows" => Some(Box::new(WindowsClipboard)),
+        #[cfg(unix)]
+        "wl-clipboard" => Some(Box::new(WlClipboard)),
+        #[cfg(unix)]
+        "xclip" => Some(Box::new(Xclip)),
+        #[cfg(unix)]
+        "xsel" => Some(Box::new(Xsel)),
+        _ => None,
+    }
+}
+
+/// An in-memory clipboard. It works everywhere, even without a display.
+///
+/// The contents are shared inside the process (i.e., all `Memory` instances
+/// see the same clipboard), but they are not visible to other programs.
+///
+/// Each selection holds one piece of data (with its MIME type).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Memory;
+
+// selection -> (MIME type, data)
+static MEMORY: Mutex<BTreeMap<Selection, (String, Vec<u8>)>> = Mutex::new(BTreeMap::new());
+
+impl Backend for Memory {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn set_contents(
+        &self,
+        data: &[u8],
+        mime: &str,
+        selection: Selection,
+    ) -> Result<(), &'static str> {
+        let mut memory = MEMORY.lock().unwrap();
+        for &sel in selection.for_writing() {
+            memory.insert(sel, (mime.to_string(), data.to_vec()));
+        }
+        Ok(())
+    }
+
+    fn get_contents(&self, mime: &str, selection: Selection) -> Result<Vec<u8>, &'static str> {
+        match MEMORY.lock().unwrap().get(&selection.for_reading()) {
+            Some((stored, data)) if base_type(stored) == base_type(mime) => Ok(data.clone()),
+            None if is_text(mime) => Ok(vec![]), // an empty clipboard
+            _ => Err(NO_SUCH_TYPE),
+        }
+    }
+
+    fn available_types(&self, selection: Selection) -> Result<Vec<String>, &'static str> {
+        let memory = MEMORY.lock().unwrap();
+        let types = memory
+            .get(&selection.for_reading())
+            .map(|(mime, _)| mime.clone());
+        Ok(types.into_iter().collect())
+    }
+}
+
 ////////////////////////////////
 // Windows-specific functions //
 ////////////////////////////////
 
 #[cfg(windows)]
-use clipboard_win::formats::Unicode;
+use clipboard_win::formats::{RawData, Unicode};
 #[cfg(windows)]
-use clipboard_win::{Clipboard, Getter, Setter};
+use clipboard_win::{raw, Clipboard, Getter, Setter};
+
+/// The Windows clipboard.
+///
+/// Windows has a single clipboard, thus the selection is ignored.
+#[cfg(windows)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WindowsClipboard;
+
+#[cfg(windows)]
+impl Backend for WindowsClipboard {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn set_contents(
+        &self,
+        data: &[u8],
+        mime: &str,
+        _selection: Selection,
+    ) -> Result<(), &'static str> {
+        if is_text(mime) {
+            let text = std::str::from_utf8(data).map_err(|_| NOT_UTF8)?;
+            return set_text_windows(text);
+        }
+        set_raw_windows(data, mime)
+    }
+
+    fn get_contents(&self, mime: &str, _selection: Selection) -> Result<Vec<u8>, &'static str> {
+        if is_text(mime) {
+            return get_text_windows().map(String::into_bytes);
+        }
+        get_raw_windows(mime)
+    }
+}
+
+/// Returns the clipboard format used for the given MIME type (registering it if needed).
+#[cfg(windows)]
+fn windows_format(mime: &str) -> Result<u32, &'static str> {
+    let name = match base_type(mime).as_str() {
+        "text/html" => "HTML Format".to_string(),
+        "image/png" => "PNG".to_string(),
+        other => other.to_string(),
+    };
+    match raw::register_format(&name) {
+        Some(format) => Ok(format.get()),
+        None => Err("cannot register clipboard format"),
+    }
+}
+
+#[cfg(windows)]
+fn set_raw_windows(data: &[u8], mime: &str) -> Result<(), &'static str> {
+    let format = windows_format(mime)?;
+    let _clip = Clipboard::new_attempts(10).expect("Open clipboard");
+    match RawData(format).write_clipboard(&data) {
+        Ok(_) => Ok(()),
+        _ => Err("cannot write to clipboard"),
+    }
+}
+
+#[cfg(windows)]
+fn get_raw_windows(mime: &str) -> Result<Vec<u8>, &'static str> {
+    let format = windows_format(mime)?;
+    let _clip = Clipboard::new_attempts(10).expect("Open clipboard");
+    let mut output = Vec::new();
+    match RawData(format).read_clipboard(&mut output) {
+        Ok(_) => Ok(output),
+        _ => Err(NO_SUCH_TYPE),
+    }
+}
 
 #[cfg(windows)]
 fn set_text_windows(text: &str) -> Result<(), &'static str> {
@@ -161,46 +863,232 @@ fn get_text_windows() -> Result<String, &'static str> {
 #[cfg(unix)]
 use std::error::Error;
 #[cfg(unix)]
+use std::io::Write;
+#[cfg(unix)]
 use std::process::{Command, Stdio};
 
 #[cfg(unix)]
-fn set_text_linux(text: &str) -> Result<(), Box<dyn Error>> {
-    set_text_with_xsel(text, "-pi")?; // primary
-    set_text_with_xsel(text, "-bi")?; // clipboard
-    Ok(())
+const WRITE_ERROR: &str = "Error: cannot write to clipboard";
+#[cfg(unix)]
+const READ_ERROR: &str = "Error: cannot read from clipboard";
+
+/// The Wayland clipboard, through the programs `wl-copy` and `wl-paste`.
+#[cfg(unix)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WlClipboard;
+
+#[cfg(unix)]
+impl Backend for WlClipboard {
+    fn name(&self) -> &'static str {
+        "wl-clipboard"
+    }
+
+    fn set_contents(
+        &self,
+        data: &[u8],
+        mime: &str,
+        selection: Selection,
+    ) -> Result<(), &'static str> {
+        for &sel in selection.for_writing() {
+            let mut args = vec![];
+            if sel == Selection::Primary {
+                args.push("--primary");
+            }
+            if !is_text(mime) {
+                args.extend(["--type", mime]);
+            }
+            write_to_command("wl-copy", &args, data).map_err(|_| WRITE_ERROR)?;
+        }
+        Ok(())
+    }
+
+    fn get_contents(&self, mime: &str, selection: Selection) -> Result<Vec<u8>, &'static str> {
+        let mut args = vec!["--no-newline"];
+        if selection.for_reading() == Selection::Primary {
+            args.push("--primary");
+        }
+        if !is_text(mime) {
+            args.extend(["--type", mime]);
+        }
+        read_from_command("wl-paste", &args, Some("Nothing is copied")).map_err(|_| READ_ERROR)
+    }
+
+    fn available_types(&self, selection: Selection) -> Result<Vec<String>, &'static str> {
+        let mut args = vec!["--list-types"];
+        if selection.for_reading() == Selection::Primary {
+            args.push("--primary");
+        }
+        let data = read_from_command("wl-paste", &args, Some("Nothing is copied"))
+            .map_err(|_| READ_ERROR)?;
+        let text = String::from_utf8_lossy(&data);
+        Ok(text.lines().map(|line| line.to_string()).collect())
+    }
 }
 
+/// The X clipboard, through the program `xclip`.
 #[cfg(unix)]
-fn set_text_with_xsel(text: &str, which_clipboard: &str) -> Result<(), Box<dyn Error>> {
-    let mut echo_output_child = Command::new("echo")
-        .arg("-n")
-        .arg(text)
-        .stdout(Stdio::piped())
-        .spawn()?;
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Xclip;
 
-    echo_output_child.wait()?;
+#[cfg(unix)]
+impl Backend for Xclip {
+    fn name(&self) -> &'static str {
+        "xclip"
+    }
 
-    if let Some(echo_output) = echo_output_child.stdout.take() {
-        let mut xsel_output_child = Command::new("xsel")
-            .arg(which_clipboard) // difference here
-            .stdin(echo_output)
-            .spawn()?;
+    fn set_contents(
+        &self,
+        data: &[u8],
+        mime: &str,
+        selection: Selection,
+    ) -> Result<(), &'static str> {
+        for &sel in selection.for_writing() {
+            let mut args = vec!["-selection", x_selection(sel), "-i"];
+            if !is_text(mime) {
+                args.extend(["-t", mime]);
+            }
+            write_to_command("xclip", &args, data).map_err(|_| WRITE_ERROR)?;
+        }
+        Ok(())
+    }
 
-        xsel_output_child.wait()?;
+    fn get_contents(&self, mime: &str, selection: Selection) -> Result<Vec<u8>, &'static str> {
+        let mut args = vec!["-selection", x_selection(selection.for_reading()), "-o"];
+        if !is_text(mime) {
+            args.extend(["-t", mime]);
+        }
+        read_from_command("xclip", &args, Some("not available")).map_err(|_| READ_ERROR)
     }
 
-    Ok(())
+    fn available_types(&self, selection: Selection) -> Result<Vec<String>, &'static str> {
+        let sel = x_selection(selection.for_reading());
+        let args = ["-selection", sel, "-t", "TARGETS", "-o"];
+        let data =
+            read_from_command("xclip", &args, Some("not available")).map_err(|_| READ_ERROR)?;
+        // the targets are X atoms; keep the MIME types only
+        let mut types: Vec<String> = vec![];
+        for target in String::from_utf8_lossy(&data).lines() {
+            let mime = match target {
+                "UTF8_STRING" | "STRING" | "TEXT" => MIME_TEXT,
+                _ if target.contains('/') => target,
+                _ => continue,
+            };
+            if !types.iter().any(|t| t == mime) {
+                types.push(mime.to_string());
+            }
+        }
+        Ok(types)
+    }
+}
+
+/// The X clipboard, through the program `xsel`. It supports plain text only.
+#[cfg(unix)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Xsel;
+
+#[cfg(unix)]
+impl Backend for Xsel {
+    fn name(&self) -> &'static str {
+        "xsel"
+    }
+
+    fn set_contents(
+        &self,
+        data: &[u8],
+        mime: &str,
+        selection: Selection,
+    ) -> Result<(), &'static str> {
+        if !is_text(mime) {
+            return Err(NOT_SUPPORTED);
+        }
+        for &sel in selection.for_writing() {
+            let which_clipboard = if sel == Selection::Primary {
+                "-pi"
+            } else {
+                "-bi"
+            };
+            set_text_with_xsel(data, which_clipboard).map_err(|_| WRITE_ERROR)?;
+        }
+        Ok(())
+    }
+
+    fn get_contents(&self, mime: &str, selection: Selection) -> Result<Vec<u8>, &'static str> {
+        if !is_text(mime) {
+            return Err(NOT_SUPPORTED);
+        }
+        let which_clipboard = if selection.for_reading() == Selection::Primary {
+            "-po"
+        } else {
+            "-bo"
+        };
+        read_from_command("xsel", &[which_clipboard], None).map_err(|_| READ_ERROR)
+    }
 }
 
+// The text is written to the stdin of xsel (and not passed as a command-line
+// argument), thus there's no size limit (ARG_MAX) and no escaping problem.
 #[cfg(unix)]
-fn get_text_linux() -> Result<String, Box<dyn Error>> {
-    let output_child = Command::new("xsel")
-        .arg("-bo")
-        .stdout(Stdio::piped())
+fn set_text_with_xsel(text: &[u8], which_clipboard: &str) -> Result<(), Box<dyn Error>> {
+    write_to_command("xsel", &[which_clipboard], text)
+}
+
+/// The name of the selection for xclip.
+#[cfg(unix)]
+fn x_selection(selection: Selection) -> &'static str {
+    match selection {
+        Selection::Primary => "primary",
+        _ => "clipboard",
+    }
+}
+
+/// Writes the data to the stdin of the command and waits for the command to complete.
+///
+/// The stdout and stderr of the command are discarded. It matters, since the clipboard
+/// tools fork a background process that keeps the selection (and would keep our pipes open).
+#[cfg(unix)]
+fn write_to_command(program: &str, args: &[&str], data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
         .spawn()?;
 
-    let prg_stdout = output_child.wait_with_output()?;
-    Ok(String::from_utf8(prg_stdout.stdout)?)
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data)?;
+    } // stdin is closed here, thus the command gets EOF
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+    Ok(())
+}
+
+/// Runs the command and returns its stdout.
+///
+/// If the command fails and its stderr contains `empty_msg`, the clipboard
+/// is considered empty (and an empty output is returned).
+#[cfg(unix)]
+fn read_from_command(
+    program: &str,
+    args: &[&str],
+    empty_msg: Option<&str>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+    if let Some(msg) = empty_msg {
+        if String::from_utf8_lossy(&output.stderr).contains(msg) {
+            return Ok(vec![]);
+        }
+    }
+    Err(format!("{} exited with {}", program, output.status).into())
 }
 
 // ==========================================================================
@@ -209,14 +1097,35 @@ fn get_text_linux() -> Result<String, Box<dyn Error>> {
 mod tests {
     use super::*;
 
+    // the tests that use the contents of the in-memory clipboard must not run in parallel
+    static MEMORY_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock_memory() -> std::sync::MutexGuard<'static, ()> {
+        MEMORY_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     #[test]
     fn check_test() {
-        assert_eq!(check(), ());
+        let _lock = lock_memory();
+        set_backend(Memory);
+        assert_eq!(check(), Ok(()));
+        //
+        reset_backend();
+        let detected = detect_backend();
+        assert_eq!(check().is_ok(), detected.is_some());
+        if let Some(detected) = detected {
+            // the detected backend is kept
+            assert_eq!(current_backend().unwrap().name(), detected.name());
+            assert!(BACKEND.read().unwrap().is_some());
+        }
+        set_backend(Memory);
     }
 
     #[test]
     fn set_text_and_get_text_test() {
-        check();
+        let _lock = lock_memory();
+        set_backend(Memory);
+        check().unwrap();
         let backup = get_text().unwrap();
         //
         for &s in &["", "a", "aa", "hello", "rust is cool", "Éva"] {
@@ -227,4 +1136,223 @@ mod tests {
         set_text(&backup).unwrap();
         assert_eq!(get_text().unwrap(), backup);
     }
+
+    #[test]
+    fn backend_by_name_test() {
+        assert_eq!(backend_by_name("memory").unwrap().name(), "memory");
+        if cfg!(unix) {
+            for name in ["wl-clipboard", "xclip", "xsel"] {
+                assert_eq!(backend_by_name(name).unwrap().name(), name);
+            }
+        }
+        assert!(backend_by_name("nothing").is_none());
+    }
+
+    #[test]
+    fn selection_test() {
+        let _lock = lock_memory();
+        set_backend(Memory);
+        //
+        set_text_to("p", Selection::Primary).unwrap();
+        set_text_to("c", Selection::Clipboard).unwrap();
+        assert_eq!(get_text_from(Selection::Primary).unwrap(), "p");
+        assert_eq!(get_text_from(Selection::Clipboard).unwrap(), "c");
+        assert_eq!(get_text_from(Selection::Both).unwrap(), "c");
+        assert_eq!(get_text().unwrap(), "c");
+        //
+        set_text_to("both", Selection::Both).unwrap();
+        assert_eq!(get_text_from(Selection::Primary).unwrap(), "both");
+        assert_eq!(get_text_from(Selection::Clipboard).unwrap(), "both");
+        //
+        set_text("text").unwrap(); // on both selections
+        assert_eq!(get_text_from(Selection::Primary).unwrap(), "text");
+        assert_eq!(get_text_from(Selection::Clipboard).unwrap(), "text");
+    }
+
+    #[test]
+    fn mime_test() {
+        let _lock = lock_memory();
+        set_backend(Memory);
+        //
+        let html = "<p>Éva</p>".as_bytes();
+        set_contents(html, MIME_HTML, Selection::Clipboard).unwrap();
+        assert_eq!(get_contents(MIME_HTML, Selection::Clipboard).unwrap(), html);
+        assert_eq!(
+            get_contents("Text/HTML; charset=utf-8", Selection::Both).unwrap(),
+            html
+        );
+        assert_eq!(available_types(Selection::Clipboard).unwrap(), [MIME_HTML]);
+        assert_eq!(get_text(), Err(NO_SUCH_TYPE));
+        //
+        let png = [0x89, b'P', b'N', b'G', 0, 0xff, 0xfe, b'\n'];
+        set_contents(&png, MIME_PNG, Selection::Primary).unwrap();
+        assert_eq!(get_contents(MIME_PNG, Selection::Primary).unwrap(), png);
+        assert_eq!(get_contents(MIME_HTML, Selection::Clipboard).unwrap(), html);
+        assert_eq!(
+            get_contents(MIME_PNG, Selection::Clipboard),
+            Err(NO_SUCH_TYPE)
+        );
+        //
+        let uris = b"file:///tmp/a.txt\r\nfile:///tmp/b%20c.txt\r\n";
+        set_contents(uris, MIME_URI_LIST, Selection::Both).unwrap();
+        assert_eq!(
+            get_contents(MIME_URI_LIST, Selection::Primary).unwrap(),
+            uris
+        );
+        assert_eq!(
+            get_contents(MIME_URI_LIST, Selection::Clipboard).unwrap(),
+            uris
+        );
+        //
+        set_contents(&[0xff, 0xfe], MIME_TEXT, Selection::Clipboard).unwrap();
+        assert_eq!(get_text_from(Selection::Clipboard), Err(NOT_UTF8));
+        set_text("plain").unwrap();
+        assert_eq!(
+            get_contents("text/plain;charset=utf-8", Selection::Both).unwrap(),
+            b"plain"
+        );
+    }
+
+    #[test]
+    fn watcher_poll_test() {
+        let _lock = lock_memory();
+        set_backend(Memory);
+        set_text("initial").unwrap();
+        //
+        let mut poller = Poller::new(&Watcher::new().history(2));
+        assert_eq!(poller.poll(), None); // the initial value is not reported
+        set_text("a").unwrap();
+        assert_eq!(poller.poll(), Some("a".to_string()));
+        assert_eq!(poller.poll(), None);
+        set_text("b").unwrap();
+        assert_eq!(poller.poll(), Some("b".to_string()));
+        set_text("a").unwrap();
+        assert_eq!(poller.poll(), Some("a".to_string()));
+        assert_eq!(poller.history(), ["b", "a"]);
+        //
+        set_contents(b"<b>x</b>", MIME_HTML, Selection::Both).unwrap();
+        assert_eq!(poller.poll(), None); // not text
+        set_text_to("primary", Selection::Primary).unwrap();
+        assert_eq!(poller.poll(), None); // another selection
+    }
+
+    #[test]
+    fn watcher_debounce_test() {
+        let _lock = lock_memory();
+        set_backend(Memory);
+        set_text("initial").unwrap();
+        //
+        let mut poller = Poller::new(&Watcher::new().debounce(0.05).history(10));
+        set_text("a").unwrap();
+        assert_eq!(poller.poll(), None);
+        set_text("ab").unwrap();
+        assert_eq!(poller.poll(), None);
+        set_text("abc").unwrap();
+        assert_eq!(poller.poll(), None);
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(poller.poll(), Some("abc".to_string()));
+        //
+        set_text("x").unwrap();
+        assert_eq!(poller.poll(), None);
+        set_text("abc").unwrap(); // changed back
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(poller.poll(), None);
+        assert_eq!(poller.history(), ["abc"]);
+    }
+
+    #[test]
+    fn watcher_iter_and_spawn_test() {
+        let _lock = lock_memory();
+        set_backend(Memory);
+        set_text_to("initial", Selection::Both).unwrap();
+        //
+        let mut changes = Watcher::new()
+            .selection(Selection::Primary)
+            .interval(0.01)
+            .iter();
+        set_text_to("p1", Selection::Primary).unwrap();
+        assert_eq!(changes.next().unwrap(), "p1");
+        assert_eq!(changes.history(), Vec::<String>::new());
+        //
+        let seen = Arc::new(Mutex::new(vec![]));
+        let seen2 = Arc::clone(&seen);
+        let handle = Watcher::new()
+            .interval(0.01)
+            .history(1)
+            .spawn(move |text| seen2.lock().unwrap().push(text.to_string()));
+        for text in ["one", "two"] {
+            set_text(text).unwrap();
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(handle.stop(), ["two"]);
+        assert_eq!(*seen.lock().unwrap(), ["one", "two"]);
+    }
+
+    #[test]
+    fn base_type_test() {
+        assert_eq!(base_type("text/plain"), "text/plain");
+        assert_eq!(base_type("Text/Plain; charset=utf-8"), "text/plain");
+        assert_eq!(base_type(" image/png "), "image/png");
+        assert_eq!(base_type(""), "");
+        //
+        assert_eq!(is_text("text/plain;charset=utf-8"), true);
+        assert_eq!(is_text(MIME_HTML), false);
+    }
+
+    #[test]
+    fn detected_backend_test() {
+        // the real clipboard (if there's one), not the one set by the other tests
+        let backend = match detect_backend() {
+            Some(backend) => backend,
+            None => return,
+        };
+        let backup = backend.get_text().unwrap();
+        for s in test_payloads() {
+            backend.set_text(&s).unwrap();
+            assert!(backend.get_text().unwrap() == s);
+        }
+        let html = b"<b>bold</b>";
+        if backend.set_contents(html, MIME_HTML, Selection::Primary) != Err(NOT_SUPPORTED) {
+            let read = backend.get_contents(MIME_HTML, Selection::Primary).unwrap();
+            assert_eq!(read, html);
+        }
+        backend.set_text(&backup).unwrap();
+    }
+
+    /// Texts that are problematic on a command line or for `echo`.
+    fn test_payloads() -> Vec<String> {
+        vec![
+            "".to_string(),
+            "hello".to_string(),
+            "Éva".to_string(),
+            "-n".to_string(),
+            "-e -n --help".to_string(),
+            r"back\slash \n \t \c \0101 end".to_string(),
+            "line1\nline2\r\n\ttabbed\n\n".to_string(),
+            "nul\0byte \u{1} \u{1b}[31mred\u{1b}[0m \u{7f}".to_string(),
+            "ünïcödé ő ű 日本語 😀 👨‍👩‍👧 \u{feff}\u{200b}".to_string(),
+            // several megabytes (way beyond ARG_MAX)
+            "0123456789abcdé😀\n".repeat(250_000),
+        ]
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_to_command_and_read_from_command_test() {
+        if which("sh").is_err() || which("cat").is_err() {
+            return;
+        }
+        let fname = "out.20261018c.txt";
+        let script = format!("cat > {}", fname);
+        for s in test_payloads() {
+            write_to_command("sh", &["-c", &script], s.as_bytes()).unwrap();
+            assert!(std::fs::read(fname).unwrap() == s.as_bytes());
+            assert!(read_from_command("cat", &[fname], None).unwrap() == s.as_bytes());
+        }
+        std::fs::remove_file(fname).unwrap();
+        //
+        assert!(write_to_command("false", &[], b"abc").is_err());
+        assert!(read_from_command("false", &[], None).is_err());
+        assert!(read_from_command("nothing20261018", &[], None).is_err());
+    }
 }
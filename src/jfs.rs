@@ -85,10 +85,10 @@ pub fn open(fname: &str) -> io::Result<BufReader<File>> {
 /// let fname = "out.20220801a.txt";
 /// let names = vec!["Alan", "Bob", "Carol"];
 /// let mut f = jabba_lib::jfs::open_for_write(fname).unwrap();
-/// f.write(b"# it is safe to delete this file\n").unwrap();
+/// f.write_all(b"# it is safe to delete this file\n").unwrap();
 /// for name in names.iter() {
-///     f.write(name.as_bytes()).unwrap();
-///     f.write(b"\n").unwrap();
+///     f.write_all(name.as_bytes()).unwrap();
+///     f.write_all(b"\n").unwrap();
 /// }
 /// fs::remove_file(fname).unwrap();
 /// ```
@@ -147,8 +147,8 @@ mod tests {
         let text = "something";
         {
             let mut f = open_for_write(fname).unwrap();
-            f.write(text.as_bytes()).unwrap();
-            f.write(b"\n").unwrap();
+            f.write_all(text.as_bytes()).unwrap();
+            f.write_all(b"\n").unwrap();
         }
         let content = read(fname).unwrap();
         assert_eq!(content, "something\n");
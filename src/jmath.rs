@@ -1,7 +1,9 @@
 //! math
 
 use crate::jvec;
-use num_bigint::BigInt;
+use num_bigint::{BigInt, Sign};
+use num_traits::{CheckedAdd, CheckedEuclid, CheckedMul, CheckedSub, NumOps, One, Signed, Zero};
+use rand::Rng;
 
 /// Returns `true` if the given number is palindrome.
 ///
@@ -20,8 +22,9 @@ pub fn is_palindrome(number: i32) -> bool {
 
 /// Returns `true` if the given number is prime.
 ///
-/// Note that this solution is not efficient. If you want to test
-/// lots of and/or large numbers, use a more efficient solution.
+/// It uses a deterministic Miller-Rabin test: with the first 12 primes as
+/// witnesses, the answer is exact for every `u64`. The modular multiplications
+/// are done in `u128`, so they cannot overflow.
 ///
 /// # Examples
 ///
@@ -30,61 +33,344 @@ pub fn is_palindrome(number: i32) -> bool {
 /// let answer = jabba_lib::jmath::is_prime(number);
 ///
 /// assert_eq!(answer, true);
+/// assert_eq!(jabba_lib::jmath::is_prime(18_446_744_073_709_551_557), true); // the largest u64 prime
 /// ```
 pub fn is_prime(n: u64) -> bool {
     if n < 2 {
         return false;
     }
-    if n == 2 {
+    for p in WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < 41 * 41 {
         return true;
     }
-    if n % 2 == 0 {
+    // else
+    // n - 1 = d * 2^s, where d is odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    WITNESSES.iter().all(|&a| {
+        let mut x = mod_pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = x.mul_mod(&x, &n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Returns `true` if the given number is (very probably) prime.
+///
+/// It uses the Miller-Rabin test with `rounds` random witnesses. If the number
+/// is prime, the answer is always `true`. If it's composite, the chance of a wrong
+/// answer is at most 4<sup>-rounds</sup> (e.g. 20 rounds are plenty).
+/// Numbers that fit in a `u64` are tested exactly (see [`is_prime`]).
+///
+/// # Examples
+///
+/// ```
+/// use num_bigint::BigInt;
+///
+/// let mersenne = (BigInt::from(1) << 127) - 1; // 2^127 - 1
+/// assert_eq!(jabba_lib::jmath::is_prime_bigint(&mersenne, 20), true);
+/// assert_eq!(jabba_lib::jmath::is_prime_bigint(&(mersenne + 2), 20), false);
+/// ```
+pub fn is_prime_bigint(n: &BigInt, rounds: usize) -> bool {
+    if n.sign() == Sign::Minus {
         return false;
     }
-
-    let mut i: u64 = 3;
-    let maxi: f64 = (n as f64).sqrt() + 1.0;
-    while (i as f64) <= maxi {
-        if n % i == 0 {
+    if let Ok(small) = u64::try_from(n) {
+        return is_prime(small);
+    }
+    // else
+    for p in WITNESSES {
+        if (n % p).is_zero() {
             return false;
         }
-        i += 2;
     }
-
-    true
+    let one = BigInt::from(1);
+    let n_minus_1 = n - &one;
+    let s = n_minus_1.trailing_zeros().unwrap();
+    let d = &n_minus_1 >> s;
+    let mut rng = rand::thread_rng();
+    (0..rounds).all(|_| {
+        // a random witness from [2, n - 2]
+        let mut bytes = n.to_bytes_be().1;
+        rng.fill(&mut bytes[..]);
+        let a: BigInt = BigInt::from_bytes_be(Sign::Plus, &bytes) % (n - 3u32) + 2u32;
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_minus_1 {
+                return true;
+            }
+        }
+        false
+    })
 }
 
+/// The witnesses of the deterministic Miller-Rabin test (enough for n < 3.3 * 10^24).
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
 //-------------------------------------
 
+/// An infinite iterator over the prime numbers: 2, 3, 5, 7, 11, ...
+///
+/// It's backed by a segmented sieve of Eratosthenes: the numbers are sieved in
+/// fixed-size segments, thus the memory usage stays small (it only grows with the square
+/// root of the current prime, up to a limit). It stops after the largest prime that fits
+/// in a `u64`.
+///
+/// # Examples
+///
+/// ```
+/// let primes: Vec<u64> = jabba_lib::jmath::Primes::new().take(5).collect();
+///
+/// assert_eq!(primes, vec![2, 3, 5, 7, 11]);
+/// assert_eq!(jabba_lib::jmath::Primes::new().nth(999_999), Some(15_485_863));
+/// ```
 #[derive(Debug)]
 pub struct Primes {
-    value: u64,
+    /// the primes of the current segment
+    segment: Vec<u64>,
+    /// the index of the next prime in `segment`
+    index: usize,
+    /// the start of the next segment
+    low: u64,
+    /// the odd primes up to `base_limit`, for sieving the segments
+    base: Vec<u64>,
+    base_limit: u64,
 }
 
 impl Primes {
     pub fn new() -> Primes {
-        Primes { value: 1 }
+        Primes {
+            segment: vec![],
+            index: 0,
+            low: 0,
+            base: vec![],
+            base_limit: 0,
+        }
+    }
+}
+
+impl Default for Primes {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Iterator for Primes {
     type Item = u64;
 
-    // not efficient
     fn next(&mut self) -> Option<u64> {
         loop {
-            self.value += if self.value >= 3 { 2 } else { 1 };
-            if is_prime(self.value) {
-                return Some(self.value);
+            if let Some(&prime) = self.segment.get(self.index) {
+                self.index += 1;
+                return Some(prime);
+            }
+            if self.low == u64::MAX {
+                return None;
+            }
+            // else: sieve the next segment
+            let high = self.low.saturating_add(SEGMENT_SIZE);
+            let root = (high - 1).isqrt().min(SIEVE_LIMIT);
+            if self.base_limit < root {
+                // with some reserve, so that it's not recomputed for every segment
+                self.base_limit = root.saturating_mul(2).min(SIEVE_LIMIT);
+                self.base = odd_primes_up_to(self.base_limit);
             }
+            self.segment = sieve_segment(self.low, high, &self.base, self.base_limit);
+            self.index = 0;
+            self.low = high;
         }
     }
 }
 
+/// Returns the primes in the range `[lo, hi)`.
+///
+/// It uses a segmented sieve of Eratosthenes, thus it's fast and uses
+/// little memory even if the numbers are large (only the width of the range matters).
+///
+/// # Examples
+///
+/// ```
+/// let primes = jabba_lib::jmath::primes_in_range(90, 110);
+///
+/// assert_eq!(primes, vec![97, 101, 103, 107, 109]);
+/// assert_eq!(
+///     jabba_lib::jmath::primes_in_range(u64::MAX - 100, u64::MAX),
+///     vec![18_446_744_073_709_551_521, 18_446_744_073_709_551_533, 18_446_744_073_709_551_557]
+/// );
+/// ```
+pub fn primes_in_range(lo: u64, hi: u64) -> Vec<u64> {
+    if lo >= hi {
+        return vec![];
+    }
+    // else
+    let base_limit = (hi - 1).isqrt().min(SIEVE_LIMIT);
+    let base = odd_primes_up_to(base_limit);
+    let mut result = vec![];
+    let mut low = lo;
+    while low < hi {
+        let high = low.saturating_add(SEGMENT_SIZE).min(hi);
+        result.extend(sieve_segment(low, high, &base, base_limit));
+        low = high;
+    }
+    result
+}
+
+/// The width of the segments of the segmented sieve.
+const SEGMENT_SIZE: u64 = 1 << 18;
+
+/// The largest base prime used for sieving. Above SIEVE_LIMIT², the numbers
+/// that survive the sieve are checked with `is_prime`.
+const SIEVE_LIMIT: u64 = 1 << 20;
+
+/// The odd primes up to `limit` (inclusive).
+fn odd_primes_up_to(limit: u64) -> Vec<u64> {
+    get_primes_below(limit as usize + 1)
+        .into_iter()
+        .skip(1)
+        .map(|p| p as u64)
+        .collect()
+}
+
+/// The primes in `[low, high)`. `base` must contain the odd primes up to `base_limit`.
+/// If `base_limit` is below √(high - 1), the survivors of the sieve are checked with `is_prime`.
+///
+/// Only the odd numbers are sieved, one bit each.
+fn sieve_segment(low: u64, high: u64, base: &[u64], base_limit: u64) -> Vec<u64> {
+    let mut result = vec![];
+    if low <= 2 && 2 < high {
+        result.push(2);
+    }
+    let first = low.max(3) | 1; // the first odd number of the segment
+    if first >= high {
+        return result;
+    }
+    // else
+    // bit i stands for the odd number first + 2 * i
+    let count = ((high - first).div_ceil(2)) as usize;
+    let mut composite = vec![0u64; count.div_ceil(64)];
+    for &p in base {
+        let square = p * p;
+        if square >= high {
+            break;
+        }
+        // the first odd multiple of p in the segment (but not p itself)
+        let Some(mut multiple) = first.div_ceil(p).checked_mul(p) else {
+            continue;
+        };
+        multiple = multiple.max(square);
+        if multiple.is_multiple_of(2) {
+            let Some(odd) = multiple.checked_add(p) else {
+                continue;
+            };
+            multiple = odd;
+        }
+        let mut i = ((multiple - first) / 2) as usize;
+        while i < count {
+            composite[i / 64] |= 1 << (i % 64);
+            i += p as usize;
+        }
+    }
+    let complete = (high - 1).isqrt() <= base_limit;
+    for i in 0..count {
+        if composite[i / 64] & (1 << (i % 64)) == 0 {
+            let n = first + 2 * i as u64;
+            if complete || is_prime(n) {
+                result.push(n);
+            }
+        }
+    }
+    result
+}
+
 //-------------------------------------
 
+/// Returns the prime factorization of the given number as `(prime, exponent)` pairs,
+/// in increasing order of the primes.
+///
+/// Small factors are found by trial division, the large ones with Pollard-Brent's rho
+/// algorithm (and Miller-Rabin for the primality checks), thus it's fast even
+/// for the product of two large primes.
+///
+/// # Panics
+///
+/// If the number is 0.
+///
+/// # Examples
+///
+/// ```
+/// let answer = jabba_lib::jmath::factorize(360);
+///
+/// assert_eq!(answer, vec![(2, 3), (3, 2), (5, 1)]);
+/// assert_eq!(jabba_lib::jmath::factorize(1), vec![]);
+/// assert_eq!(
+///     jabba_lib::jmath::factorize(18_446_744_030_759_878_681), // 4294967291 * 4294967291
+///     vec![(4_294_967_291, 2)]
+/// );
+/// ```
+pub fn factorize(number: u64) -> Vec<(u64, u32)> {
+    assert!(number > 0, "the number must be positive");
+
+    let mut factors = vec![];
+    let mut n = number;
+    // the composite divisors never divide n, their prime factors were removed before
+    for d in 2..TRIAL_LIMIT {
+        if d * d > n {
+            break;
+        }
+        while n.is_multiple_of(d) {
+            n /= d;
+            factors.push(d);
+        }
+    }
+    // the remaining part has no factor below TRIAL_LIMIT
+    let mut todo = vec![n];
+    while let Some(n) = todo.pop() {
+        if n == 1 {
+            continue;
+        }
+        if n < TRIAL_LIMIT * TRIAL_LIMIT || is_prime(n) {
+            factors.push(n);
+        } else {
+            let d = pollard_brent(n);
+            todo.push(d);
+            todo.push(n / d);
+        }
+    }
+    factors.sort_unstable();
+
+    let mut result: Vec<(u64, u32)> = vec![];
+    for p in factors {
+        match result.last_mut() {
+            Some((prime, exp)) if *prime == p => *exp += 1,
+            _ => result.push((p, 1)),
+        }
+    }
+    result
+}
+
 /// Returns the prime divisors of the given number.
 ///
+/// A prime appears as many times as it divides the number (see also [`factorize`]).
+///
+/// # Panics
+///
+/// If the number is 0.
+///
 /// # Examples
 ///
 /// ```
@@ -92,21 +378,61 @@ impl Iterator for Primes {
 /// let answer = jabba_lib::jmath::get_prime_divisors(number);
 ///
 /// assert_eq!(answer, vec![5, 7, 13, 29]);
+/// assert_eq!(jabba_lib::jmath::get_prime_divisors(12), vec![2, 2, 3]);
 /// ```
 pub fn get_prime_divisors(number: u64) -> Vec<u64> {
-    let mut result = vec![];
+    factorize(number)
+        .into_iter()
+        .flat_map(|(prime, exp)| std::iter::repeat_n(prime, exp as usize))
+        .collect()
+}
 
-    let mut n = number;
-    let mut primes = Primes::new();
-    while n != 1 {
-        let prime = primes.next().unwrap();
-        while n % prime == 0 {
-            n /= prime;
-            result.push(prime);
+/// The limit of the trial division in `factorize`.
+const TRIAL_LIMIT: u64 = 1000;
+
+/// Returns a non-trivial divisor of `n`, which must be an odd composite number.
+///
+/// It's Brent's variant of Pollard's rho algorithm, with the polynomial x² + c.
+/// If a round fails, it's repeated with the next c.
+fn pollard_brent(n: u64) -> u64 {
+    // the products of the differences are accumulated, and gcd() is called only after M steps
+    const M: u64 = 128;
+    for c in 1.. {
+        let f = |x: u64| ((x as u128 * x as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut ys) = (0, 2, 0);
+        let (mut q, mut g, mut r) = (1, 1, 1);
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..M.min(r - k) {
+                    y = f(y);
+                    q = q.mul_mod(&x.abs_diff(y), &n);
+                }
+                g = gcd(q, n);
+                k += M;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // the product became 0 mod n, go back and find the factor step by step
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
         }
     }
-
-    result
+    unreachable!()
 }
 
 /// Returns the digits of the given number.
@@ -159,7 +485,8 @@ pub fn digits_from_str(s: &str) -> Vec<i32> {
 
 /// Returns all the primes below the given number.
 ///
-/// The method uses Aristotle's sieve algorithm.
+/// The method uses Eratosthenes' sieve algorithm. The sieve is bit-packed and
+/// contains the odd numbers only, thus it needs `size / 16` bytes of memory.
 ///
 /// # Examples
 ///
@@ -169,29 +496,42 @@ pub fn digits_from_str(s: &str) -> Vec<i32> {
 /// assert_eq!(primes, vec![2, 3, 5, 7]);
 /// ```
 pub fn get_primes_below(size: usize) -> Vec<usize> {
-    let mut v = vec![true; size];
-    v[0] = false; // 0 is not prime
-    v[1] = false; // 1 is not prime
-
-    for i in 2..size {
-        if v[i] {
-            for pos in (i + i..size).step_by(i) {
-                v[pos] = false;
+    // bit i stands for the odd number 2 * i + 1
+    let half = size / 2;
+    let mut composite = vec![0u64; half.div_ceil(64)];
+    let is_composite = |v: &[u64], i: usize| v[i / 64] & (1 << (i % 64)) != 0;
+
+    let mut i = 1;
+    while (2 * i + 1) * (2 * i + 1) < size {
+        if !is_composite(&composite, i) {
+            let p = 2 * i + 1;
+            for j in (p * p / 2..half).step_by(p) {
+                composite[j / 64] |= 1 << (j % 64);
             }
         }
+        i += 1;
     }
 
     let mut result = vec![];
-    for (i, value) in v.into_iter().enumerate() {
-        if value {
-            result.push(i);
+    if size > 2 {
+        result.push(2);
+    }
+    for i in 1..half {
+        if !is_composite(&composite, i) {
+            result.push(2 * i + 1);
         }
     }
 
     result
 }
 
-/// Returns the divisors of the given number.
+/// Returns the divisors of the given number, in increasing order.
+///
+/// The divisors are generated from the prime factorization (see [`factorize`]).
+///
+/// # Panics
+///
+/// If the number is 0.
 ///
 /// # Examples
 ///
@@ -202,20 +542,19 @@ pub fn get_primes_below(size: usize) -> Vec<usize> {
 /// assert_eq!(answer, vec![1, 2, 4, 7, 14, 28]);
 /// ```
 pub fn get_divisors(number: u64) -> Vec<u64> {
-    assert!(number > 0);
-
     let mut result = vec![1];
 
-    let half = number / 2;
-    for i in 2..half + 1 {
-        if number % i == 0 {
-            result.push(i);
+    for (prime, exp) in factorize(number) {
+        let count = result.len();
+        let mut power = 1;
+        for _ in 0..exp {
+            power *= prime;
+            for k in 0..count {
+                result.push(result[k] * power);
+            }
         }
     }
-
-    if number > 1 {
-        result.push(number);
-    }
+    result.sort_unstable();
 
     result
 }
@@ -224,6 +563,10 @@ pub fn get_divisors(number: u64) -> Vec<u64> {
 ///
 /// Proper divisors: numbers less than `n` which divide evenly into `n`.
 ///
+/// # Panics
+///
+/// If the number is 0.
+///
 /// # Examples
 ///
 /// ```
@@ -238,6 +581,155 @@ pub fn get_proper_divisors(number: u64) -> Vec<u64> {
     result
 }
 
+/// Returns the number of divisors of the given number (often denoted by τ or d).
+///
+/// # Panics
+///
+/// If the number is 0.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::tau(28), 6); // 1, 2, 4, 7, 14, 28
+/// assert_eq!(jabba_lib::jmath::tau(1), 1);
+/// ```
+pub fn tau(number: u64) -> u64 {
+    factorize(number)
+        .into_iter()
+        .map(|(_, exp)| exp as u64 + 1)
+        .product()
+}
+
+/// Returns the sum of the k-th powers of the divisors of the given number (σₖ).
+///
+/// `sigma(n, 0)` is the number of divisors, `sigma(n, 1)` is the sum of the divisors.
+///
+/// # Panics
+///
+/// If the number is 0, or if the result doesn't fit in a `u128`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::sigma(28, 1), 56); // 1 + 2 + 4 + 7 + 14 + 28
+/// assert_eq!(jabba_lib::jmath::sigma(6, 2), 50); // 1 + 4 + 9 + 36
+/// assert_eq!(jabba_lib::jmath::sigma(6, 0), 4);
+/// ```
+pub fn sigma(number: u64, k: u32) -> u128 {
+    let overflow = || panic!("sigma({}, {}) doesn't fit in a u128", number, k);
+
+    let mut result: u128 = 1;
+    for (prime, exp) in factorize(number) {
+        // 1 + p^k + p^2k + ... + p^(exp * k)
+        let pk = (prime as u128).checked_pow(k).unwrap_or_else(overflow);
+        let (mut sum, mut power): (u128, u128) = (1, 1);
+        for _ in 0..exp {
+            power = power.checked_mul(pk).unwrap_or_else(overflow);
+            sum = sum.checked_add(power).unwrap_or_else(overflow);
+        }
+        result = result.checked_mul(sum).unwrap_or_else(overflow);
+    }
+    result
+}
+
+/// Returns Euler's totient function of the given number (φ), i.e. the number of
+/// integers in `[1, n]` that are coprime to `n`.
+///
+/// # Panics
+///
+/// If the number is 0.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::totient(36), 12);
+/// assert_eq!(jabba_lib::jmath::totient(97), 96);
+/// assert_eq!(jabba_lib::jmath::totient(1), 1);
+/// ```
+pub fn totient(number: u64) -> u64 {
+    factorize(number)
+        .into_iter()
+        .map(|(prime, exp)| prime.pow(exp - 1) * (prime - 1))
+        .product()
+}
+
+/// Returns the Möbius function of the given number (μ).
+///
+/// It's 0 if the number is divisible by a square (other than 1), otherwise it's 1 or -1
+/// depending on whether the number of its prime factors is even or odd.
+///
+/// # Panics
+///
+/// If the number is 0.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::mobius(1), 1);
+/// assert_eq!(jabba_lib::jmath::mobius(30), -1); // 2 * 3 * 5
+/// assert_eq!(jabba_lib::jmath::mobius(12), 0); // 2 * 2 * 3
+/// ```
+pub fn mobius(number: u64) -> i32 {
+    let factors = factorize(number);
+    if factors.iter().any(|&(_, exp)| exp > 1) {
+        0
+    } else if factors.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+/// The classification of a number by the sum of its proper divisors (see [`abundance`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abundance {
+    /// The sum of the proper divisors is less than the number.
+    Deficient,
+    /// The sum of the proper divisors equals the number.
+    Perfect,
+    /// The sum of the proper divisors is greater than the number.
+    Abundant,
+}
+
+/// Tells whether the given number is deficient, perfect or abundant.
+///
+/// # Panics
+///
+/// If the number is 0.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jmath::Abundance;
+///
+/// assert_eq!(jabba_lib::jmath::abundance(8), Abundance::Deficient); // 1 + 2 + 4 = 7
+/// assert_eq!(jabba_lib::jmath::abundance(28), Abundance::Perfect); // 1 + 2 + 4 + 7 + 14 = 28
+/// assert_eq!(jabba_lib::jmath::abundance(12), Abundance::Abundant); // 1 + 2 + 3 + 4 + 6 = 16
+/// ```
+pub fn abundance(number: u64) -> Abundance {
+    let sum = sigma(number, 1) - number as u128;
+    match sum.cmp(&(number as u128)) {
+        std::cmp::Ordering::Less => Abundance::Deficient,
+        std::cmp::Ordering::Equal => Abundance::Perfect,
+        std::cmp::Ordering::Greater => Abundance::Abundant,
+    }
+}
+
+/// Returns true if the given number is perfect, i.e. it's the sum of its proper divisors.
+///
+/// Unlike [`abundance`], it accepts 0 (which is not perfect).
+///
+/// # Examples
+///
+/// ```
+/// let perfect: Vec<u64> = (1..10_000).filter(|&n| jabba_lib::jmath::is_perfect(n)).collect();
+///
+/// assert_eq!(perfect, vec![6, 28, 496, 8128]);
+/// ```
+pub fn is_perfect(number: u64) -> bool {
+    number > 0 && abundance(number) == Abundance::Perfect
+}
+
 /// Returns the Collatz sequence of the given number.
 ///
 /// # Examples
@@ -255,7 +747,7 @@ pub fn get_collatz_sequence(number: u64) -> Vec<u64> {
     let mut result = vec![n];
 
     while n != 1 {
-        if n % 2 == 0 {
+        if n.is_multiple_of(2) {
             n /= 2;
         } else {
             n = 3 * n + 1;
@@ -299,6 +791,343 @@ pub fn factorial_bigint(n: u128) -> BigInt {
     result
 }
 
+//-------------------------------------
+
+/// The integer types supported by the modular arithmetic functions of this module
+/// ([`gcd`], [`lcm`], [`mod_pow`], [`mod_inverse`], [`crt`], [`isqrt`], etc.).
+///
+/// It's implemented for the primitive integer types and for `BigInt`.
+pub trait Integer:
+    Clone + Ord + Zero + One + NumOps + CheckedAdd + CheckedSub + CheckedMul + CheckedEuclid
+{
+    /// Returns `(self * other) % m` without overflow. Both numbers must be in `[0, m)`.
+    fn mul_mod(&self, other: &Self, m: &Self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($wide:ty => $($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+                    (*self as $wide * *other as $wide % *m as $wide) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u128 => u8, u16, u32, u64, usize);
+impl_integer!(i128 => i8, i16, i32, i64, isize);
+
+impl Integer for u128 {
+    fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+        mul_mod_by_doubling(*self, *other, *m)
+    }
+}
+
+impl Integer for i128 {
+    fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+        mul_mod_by_doubling(*self, *other, *m)
+    }
+}
+
+impl Integer for BigInt {
+    fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+        self * other % m
+    }
+}
+
+/// Returns the greatest common divisor of `a` and `b`.
+///
+/// The result is never negative, and `gcd(0, 0)` is 0.
+/// It's like Python's `math.gcd()`.
+///
+/// # Panics
+///
+/// If the result doesn't fit in the type, e.g. `gcd(i64::MIN, 0)`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::gcd(12, 18), 6);
+/// assert_eq!(jabba_lib::jmath::gcd(-12, 18), 6);
+/// assert_eq!(jabba_lib::jmath::gcd(0u8, 0), 0);
+/// ```
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a, b);
+    while !b.is_zero() {
+        // the euclidean remainder is never negative; it only fails for MIN % -1, which is 0
+        let r = a.checked_rem_euclid(&b).unwrap_or_else(T::zero);
+        a = b;
+        b = r;
+    }
+    abs(a)
+}
+
+/// Returns the least common multiple of `a` and `b`.
+///
+/// The result is never negative, and it's 0 if one of the numbers is 0.
+/// It's like Python's `math.lcm()`.
+///
+/// # Panics
+///
+/// If the result doesn't fit in the type.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::lcm(4, 6), 12);
+/// assert_eq!(jabba_lib::jmath::lcm(-4, 6), 12);
+/// assert_eq!(jabba_lib::jmath::lcm(0, 6), 0);
+/// ```
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    if a.is_zero() || b.is_zero() {
+        return T::zero();
+    }
+    // else
+    let g = gcd(a.clone(), b.clone());
+    let a = abs(a);
+    let b = abs(b);
+    (a / g).checked_mul(&b).expect("lcm: overflow")
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y == g`, where `g` is the greatest
+/// common divisor of `a` and `b` (extended Euclidean algorithm).
+///
+/// As the coefficients can be negative, it works with the signed types only.
+///
+/// # Examples
+///
+/// ```
+/// let (g, x, y) = jabba_lib::jmath::extended_gcd(240, 46);
+///
+/// assert_eq!(g, 2);
+/// assert_eq!(240 * x + 46 * y, 2);
+/// ```
+pub fn extended_gcd<T: Integer + Signed>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while !r.is_zero() {
+        // it only fails for MIN % -1, which is 0
+        let next_r = old_r.checked_rem_euclid(&r).unwrap_or_else(T::zero);
+        if next_r.is_zero() {
+            // the next coefficients are not needed (and they could overflow)
+            (old_r, old_x, old_y) = (r, x, y);
+            break;
+        }
+        // else
+        let q = old_r.checked_div_euclid(&r).unwrap();
+        let next_x = checked_sub_mul(&old_x, &q, &x);
+        let next_y = checked_sub_mul(&old_y, &q, &y);
+        (old_r, r) = (r, next_r);
+        (old_x, x) = (x, next_x);
+        (old_y, y) = (y, next_y);
+    }
+    if old_r.is_negative() {
+        // the last non-zero remainder was a or b, which can be negative
+        (abs(old_r), T::zero() - old_x, T::zero() - old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Returns `(base ^ exp) % m`, in the range `[0, m)`.
+///
+/// The modulus must be positive and the exponent must be non-negative.
+/// The intermediate results never overflow. It's like Python's `pow(base, exp, m)`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::mod_pow(4, 13, 497), 445);
+/// assert_eq!(jabba_lib::jmath::mod_pow(-2, 3, 5), 2);
+/// assert_eq!(jabba_lib::jmath::mod_pow(u64::MAX - 1, u64::MAX, u64::MAX), u64::MAX - 1);
+/// ```
+pub fn mod_pow<T: Integer>(base: T, exp: T, m: T) -> T {
+    assert!(m > T::zero(), "mod_pow: the modulus must be positive");
+    assert!(
+        exp >= T::zero(),
+        "mod_pow: the exponent must be non-negative"
+    );
+
+    let two = T::one() + T::one();
+    let mut result = T::one() % m.clone();
+    let mut base = rem_euclid(base, &m);
+    let mut exp = exp;
+    while !exp.is_zero() {
+        if exp.clone() % two.clone() == T::one() {
+            result = result.mul_mod(&base, &m);
+        }
+        base = base.mul_mod(&base, &m);
+        exp = exp / two.clone();
+    }
+    result
+}
+
+/// Returns the modular multiplicative inverse of `a` modulo `m`, i.e. the number `x`
+/// in `[0, m)` for which `(a * x) % m == 1`.
+///
+/// Returns `None` if `a` and `m` are not coprime. The modulus must be positive.
+/// It's like Python's `pow(a, -1, m)`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::mod_inverse(3, 11), Some(4));
+/// assert_eq!(jabba_lib::jmath::mod_inverse(-3, 11), Some(7));
+/// assert_eq!(jabba_lib::jmath::mod_inverse(6, 9), None);
+/// ```
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    assert!(m > T::zero(), "mod_inverse: the modulus must be positive");
+
+    // the coefficients are kept in [0, m), thus it works with the unsigned types too
+    let (mut old_r, mut r) = (m.clone(), rem_euclid(a, &m));
+    let (mut old_t, mut t) = (T::zero(), T::one() % m.clone());
+    while !r.is_zero() {
+        let q = old_r.clone() / r.clone();
+        let next_r = old_r % r.clone();
+        let next_t = sub_mod(&old_t, &(q % m.clone()).mul_mod(&t, &m), &m);
+        (old_r, r) = (r, next_r);
+        (old_t, t) = (t, next_t);
+    }
+    if old_r == T::one() {
+        Some(old_t)
+    } else {
+        None
+    }
+}
+
+/// Solves a system of congruences with the Chinese Remainder Theorem.
+///
+/// The congruences are given as `(residue, modulus)` pairs: `x ≡ residue (mod modulus)`.
+/// The moduli must be positive, but they don't need to be pairwise coprime.
+/// Returns `Some((x, m))`, where `m` is the least common multiple of the moduli
+/// and `x` is the solution in `[0, m)`; or `None` if the system has no solution.
+///
+/// # Panics
+///
+/// If `m` doesn't fit in the type.
+///
+/// # Examples
+///
+/// ```
+/// // x ≡ 2 (mod 3), x ≡ 3 (mod 5), x ≡ 2 (mod 7)
+/// assert_eq!(jabba_lib::jmath::crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+/// assert_eq!(jabba_lib::jmath::crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+/// assert_eq!(jabba_lib::jmath::crt(&[(1, 4), (2, 6)]), None);
+/// ```
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let (mut x, mut m) = (T::zero(), T::one());
+    for (residue, modulus) in congruences {
+        assert!(*modulus > T::zero(), "crt: the moduli must be positive");
+        // x + m * k ≡ residue (mod modulus)  =>  (m / g) * k ≡ diff / g (mod modulus / g)
+        let g = gcd(m.clone(), modulus.clone());
+        let diff = sub_mod(
+            &rem_euclid(residue.clone(), modulus),
+            &rem_euclid(x.clone(), modulus),
+            modulus,
+        );
+        if !(diff.clone() % g.clone()).is_zero() {
+            return None;
+        }
+        // else
+        let reduced = modulus.clone() / g.clone();
+        let inverse = mod_inverse(m.clone() / g.clone(), reduced.clone()).unwrap();
+        let k = (diff / g).mul_mod(&inverse, &reduced);
+        // x < m and k < reduced, thus the new x is less than the new m
+        let new_m = m.checked_mul(&reduced).expect("crt: overflow");
+        x = x + m * k;
+        m = new_m;
+    }
+    Some((x, m))
+}
+
+/// Returns the integer square root of the given number, i.e. the largest integer
+/// whose square is not greater than the number.
+///
+/// The number must be non-negative. It's like Python's `math.isqrt()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jmath::isqrt(24), 4);
+/// assert_eq!(jabba_lib::jmath::isqrt(25), 5);
+/// assert_eq!(jabba_lib::jmath::isqrt(u64::MAX), 4_294_967_295);
+/// ```
+pub fn isqrt<T: Integer>(n: T) -> T {
+    assert!(n >= T::zero(), "isqrt: the number must be non-negative");
+
+    let two = T::one() + T::one();
+    if n < two {
+        return n;
+    }
+    // else
+    // Newton's method, decreasing from n; (x + n / x) / 2 is computed without overflow
+    let mut x = n.clone();
+    loop {
+        let q = n.clone() / x.clone();
+        let carry = (x.clone() % two.clone() + q.clone() % two.clone()) / two.clone();
+        let y = x.clone() / two.clone() + q / two.clone() + carry;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// |a|, or panics if it doesn't fit in the type
+fn abs<T: Integer>(a: T) -> T {
+    if a < T::zero() {
+        T::zero().checked_sub(&a).expect("overflow")
+    } else {
+        a
+    }
+}
+
+/// `a mod m` in `[0, m)`, for a positive `m`
+fn rem_euclid<T: Integer>(a: T, m: &T) -> T {
+    // a % m is in (-m, m), so adding m can't overflow
+    let r = a % m.clone();
+    if r < T::zero() {
+        r + m.clone()
+    } else {
+        r
+    }
+}
+
+/// `(a - b) mod m` without overflow, for `a` and `b` in `[0, m)`
+fn sub_mod<T: Integer>(a: &T, b: &T, m: &T) -> T {
+    if a >= b {
+        a.clone() - b.clone()
+    } else {
+        m.clone() - b.clone() + a.clone()
+    }
+}
+
+/// `(a * b) % m` with additions only, for the widest types
+fn mul_mod_by_doubling<T: Integer + Copy>(a: T, b: T, m: T) -> T {
+    // (x + y) mod m without overflow, for x and y in [0, m)
+    let add_mod = |x: T, y: T| if x >= m - y { x - (m - y) } else { x + y };
+    let two = T::one() + T::one();
+    let (mut result, mut a, mut b) = (T::zero(), a, b);
+    while !b.is_zero() {
+        if b % two == T::one() {
+            result = add_mod(result, a);
+        }
+        a = add_mod(a, a);
+        b = b / two;
+    }
+    result
+}
+
+/// `a - q * b`, or panics on overflow
+fn checked_sub_mul<T: Integer>(a: &T, q: &T, b: &T) -> T {
+    q.checked_mul(b)
+        .and_then(|product| a.checked_sub(&product))
+        .expect("overflow")
+}
+
 // ==========================================================================
 
 #[cfg(test)]
@@ -332,6 +1161,65 @@ mod tests {
         assert_eq!(is_prime(100), false);
     }
 
+    /// The trial division (the old implementation), to cross-check `is_prime`.
+    fn is_prime_trial_division(n: u64) -> bool {
+        if n < 2 {
+            return false;
+        }
+        let mut i = 2;
+        while i * i <= n {
+            if n.is_multiple_of(i) {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    #[test]
+    fn is_prime_test2() {
+        for n in 0..20_000 {
+            assert_eq!(is_prime(n), is_prime_trial_division(n), "n = {}", n);
+        }
+        // around 2^32, where the trial division is still fast enough
+        for n in (1 << 32) - 1000..(1 << 32) + 1000 {
+            assert_eq!(is_prime(n), is_prime_trial_division(n), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn is_prime_test3() {
+        assert_eq!(is_prime(u64::MAX), false);
+        assert_eq!(is_prime(18_446_744_073_709_551_557), true); // the largest u64 prime
+        assert_eq!(is_prime(4_294_967_291), true); // the largest u32 prime
+        assert_eq!(is_prime(4_294_967_291 * 4_294_967_279), false);
+        // strong pseudoprimes to many bases
+        assert_eq!(is_prime(3_215_031_751), false);
+        assert_eq!(is_prime(3_825_123_056_546_413_051), false);
+        // Carmichael numbers
+        assert_eq!(is_prime(561), false);
+        assert_eq!(is_prime(41_041), false);
+        // Mersenne primes
+        assert_eq!(is_prime((1 << 31) - 1), true);
+        assert_eq!(is_prime((1 << 61) - 1), true);
+        assert_eq!(is_prime((1 << 59) - 1), false);
+    }
+
+    #[test]
+    fn is_prime_bigint_test() {
+        for n in 0..1000 {
+            assert_eq!(is_prime_bigint(&BigInt::from(n), 10), is_prime(n));
+        }
+        assert_eq!(is_prime_bigint(&BigInt::from(-7), 10), false);
+        let two = BigInt::from(2);
+        assert_eq!(is_prime_bigint(&(two.pow(89) - 1), 20), true);
+        assert_eq!(is_prime_bigint(&(two.pow(107) - 1), 20), true);
+        assert_eq!(is_prime_bigint(&(two.pow(67) - 1), 20), false); // 193707721 * 761838257287
+        assert_eq!(is_prime_bigint(&(two.pow(64) + 13), 20), true);
+        let p = BigInt::from(18_446_744_073_709_551_557_u64);
+        assert_eq!(is_prime_bigint(&(&p * &p), 20), false);
+    }
+
     #[test]
     fn generate_primes_below_100() {
         let numbers = [
@@ -354,6 +1242,92 @@ mod tests {
         assert_eq!(get_prime_divisors(13195), [5, 7, 13, 29]);
     }
 
+    fn factorize_trial_division(number: u64) -> Vec<(u64, u32)> {
+        let mut result = vec![];
+        let mut n = number;
+        let mut d = 2;
+        while d * d <= n {
+            let mut exp = 0;
+            while n.is_multiple_of(d) {
+                n /= d;
+                exp += 1;
+            }
+            if exp > 0 {
+                result.push((d, exp));
+            }
+            d += 1;
+        }
+        if n > 1 {
+            result.push((n, 1));
+        }
+        result
+    }
+
+    #[test]
+    fn factorize_test() {
+        assert_eq!(factorize(1), []);
+        assert_eq!(factorize(2), [(2, 1)]);
+        assert_eq!(factorize(1024), [(2, 10)]);
+        assert_eq!(factorize(360), [(2, 3), (3, 2), (5, 1)]);
+        for n in 1..20_000 {
+            assert_eq!(factorize(n), factorize_trial_division(n), "n = {}", n);
+        }
+        for n in (1_000_000_000..).step_by(7919).take(300) {
+            assert_eq!(factorize(n), factorize_trial_division(n), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn factorize_test2() {
+        assert_eq!(
+            factorize(u64::MAX),
+            [
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6700417, 1)
+            ]
+        );
+        assert_eq!(factorize(18446744073709551557), [(18446744073709551557, 1)]);
+        // two 32-bit primes
+        assert_eq!(
+            factorize(4294967291 * 4294967279),
+            [(4294967279, 1), (4294967291, 1)]
+        );
+        assert_eq!(factorize(4294967291 * 4294967291), [(4294967291, 2)]);
+        assert_eq!(factorize(1000003 * 1000003 * 1000003), [(1000003, 3)]);
+        assert_eq!(factorize(1009 * 1009), [(1009, 2)]);
+        // Carmichael numbers
+        assert_eq!(factorize(561), [(3, 1), (11, 1), (17, 1)]);
+        assert_eq!(
+            factorize(3825123056546413051),
+            [(149491, 1), (747451, 1), (34233211, 1)]
+        );
+        assert_eq!(
+            factorize(2u64.pow(62) - 1),
+            [(3, 1), (715827883, 1), (2147483647, 1)]
+        );
+        assert_eq!(
+            factorize(600851475143),
+            [(71, 1), (839, 1), (1471, 1), (6857, 1)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the number must be positive")]
+    fn factorize_zero_test() {
+        factorize(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the number must be positive")]
+    fn get_prime_divisors_zero_test() {
+        get_prime_divisors(0);
+    }
+
     #[test]
     fn digits_test() {
         assert_eq!(digits(123), [1, 2, 3]);
@@ -392,6 +1366,76 @@ mod tests {
         assert_eq!(get_primes_below(100).len(), 25);
     }
 
+    #[test]
+    fn get_primes_below_test2() {
+        assert_eq!(get_primes_below(0), []);
+        assert_eq!(get_primes_below(1), []);
+        for size in 0..2000 {
+            let expected: Vec<usize> = (0..size).filter(|&n| is_prime(n as u64)).collect();
+            assert_eq!(get_primes_below(size), expected, "size = {}", size);
+        }
+        assert_eq!(get_primes_below(1_000_000).len(), 78_498);
+    }
+
+    #[test]
+    fn primes_iterator_test() {
+        let below: Vec<u64> = get_primes_below(2_000_000)
+            .into_iter()
+            .map(|p| p as u64)
+            .collect();
+        let primes: Vec<u64> = Primes::new().take(below.len()).collect();
+        assert_eq!(primes, below);
+        assert_eq!(Primes::new().nth(999_999), Some(15_485_863));
+    }
+
+    #[test]
+    fn primes_iterator_test2() {
+        // above SIEVE_LIMIT², the base primes are not recomputed for every segment
+        let lo = 1 << 41;
+        let mut primes = Primes {
+            low: lo,
+            ..Primes::new()
+        };
+        let first: Vec<u64> = primes.by_ref().take(10).collect();
+        let base = primes.base.as_ptr();
+        assert_eq!(primes.base_limit, SIEVE_LIMIT);
+        let count = primes
+            .by_ref()
+            .take_while(|&p| p < lo + 3 * SEGMENT_SIZE)
+            .count();
+        assert_eq!(primes.base.as_ptr(), base);
+        let expected: Vec<u64> = (lo..lo + 3 * SEGMENT_SIZE)
+            .filter(|&n| is_prime(n))
+            .collect();
+        assert_eq!(first, expected[..10]);
+        assert_eq!(count, expected.len() - 10);
+    }
+
+    #[test]
+    fn primes_in_range_test() {
+        assert_eq!(primes_in_range(0, 0), []);
+        assert_eq!(primes_in_range(10, 5), []);
+        assert_eq!(primes_in_range(0, 2), []);
+        assert_eq!(primes_in_range(0, 3), [2]);
+        assert_eq!(primes_in_range(2, 3), [2]);
+        assert_eq!(primes_in_range(3, 4), [3]);
+        assert_eq!(primes_in_range(0, 30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        for lo in 0..100 {
+            for hi in lo..100 {
+                let expected: Vec<u64> = (lo..hi).filter(|&n| is_prime(n)).collect();
+                assert_eq!(primes_in_range(lo, hi), expected);
+            }
+        }
+        // several segments, and large numbers
+        let lo = 1_000_000_000_000;
+        let expected: Vec<u64> = (lo..lo + 600_000).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes_in_range(lo, lo + 600_000), expected);
+        let lo = u64::MAX - 10_000;
+        let expected: Vec<u64> = (lo..u64::MAX).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes_in_range(lo, u64::MAX), expected);
+        assert_eq!(expected.last(), Some(&18_446_744_073_709_551_557));
+    }
+
     #[test]
     fn get_divisors_test() {
         assert_eq!(get_divisors(1), [1]);
@@ -414,6 +1458,323 @@ mod tests {
         assert_eq!(get_proper_divisors(28), [1, 2, 4, 7, 14]);
     }
 
+    fn get_divisors_naive(number: u64) -> Vec<u64> {
+        (1..=number).filter(|d| number.is_multiple_of(*d)).collect()
+    }
+
+    #[test]
+    fn get_divisors_test2() {
+        for n in 1..3000 {
+            let divisors = get_divisors_naive(n);
+            assert_eq!(get_divisors(n), divisors, "n = {}", n);
+            assert_eq!(tau(n), divisors.len() as u64);
+            assert_eq!(sigma(n, 0), divisors.len() as u128);
+            assert_eq!(sigma(n, 1), divisors.iter().map(|&d| d as u128).sum());
+            assert_eq!(
+                sigma(n, 3),
+                divisors.iter().map(|&d| (d as u128).pow(3)).sum()
+            );
+            let coprimes = (1..=n).filter(|&k| gcd(k, n) == 1).count();
+            assert_eq!(totient(n), coprimes as u64);
+        }
+        let divisors = get_divisors(u64::MAX);
+        assert_eq!(divisors.len(), 128);
+        assert_eq!(divisors.first(), Some(&1));
+        assert_eq!(divisors.last(), Some(&u64::MAX));
+        assert!(divisors.windows(2).all(|w| w[0] < w[1]));
+        assert!(divisors.iter().all(|&d| u64::MAX.is_multiple_of(d)));
+        assert_eq!(get_proper_divisors(4294967291), [1]);
+    }
+
+    #[test]
+    fn arithmetic_functions_test() {
+        assert_eq!(tau(1), 1);
+        assert_eq!(tau(720720), 240);
+        assert_eq!(tau(u64::MAX), 128);
+        assert_eq!(sigma(1, 5), 1);
+        assert_eq!(sigma(12, 1), 28);
+        assert_eq!(sigma(18446744073709551557, 1), 18446744073709551558);
+        assert_eq!(totient(1), 1);
+        assert_eq!(totient(18446744073709551557), 18446744073709551556);
+        assert_eq!(totient(1024), 512);
+        // mobius
+        let expected = [
+            1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0, -1, 1, 1, 0, -1, 0, -1, 0,
+        ];
+        let values: Vec<i32> = (1..=20).map(mobius).collect();
+        assert_eq!(values, expected);
+        assert_eq!(mobius(4294967291 * 4294967279), 1);
+        assert_eq!(mobius(4294967291 * 4294967291), 0);
+    }
+
+    #[test]
+    fn divisor_functions_zero_test() {
+        let functions: [fn(u64); 8] = [
+            |n| {
+                get_divisors(n);
+            },
+            |n| {
+                get_proper_divisors(n);
+            },
+            |n| {
+                tau(n);
+            },
+            |n| {
+                sigma(n, 1);
+            },
+            |n| {
+                totient(n);
+            },
+            |n| {
+                mobius(n);
+            },
+            |n| {
+                abundance(n);
+            },
+            |n| {
+                factorize(n);
+            },
+        ];
+        for f in functions {
+            let result = std::panic::catch_unwind(|| f(0));
+            let message = *result.unwrap_err().downcast::<&str>().unwrap();
+            assert_eq!(message, "the number must be positive");
+        }
+        assert!(!is_perfect(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn sigma_overflow_test() {
+        sigma(u64::MAX, 3);
+    }
+
+    #[test]
+    fn gcd_test() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(18, 12), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(12, -18), 6);
+        assert_eq!(gcd(-12, -18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(gcd(-5, 0), 5);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(17u8, 255), 17);
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(gcd(i64::MIN, -1), 1);
+        assert_eq!(gcd(i64::MIN, i64::MAX), 1);
+        assert_eq!(gcd(u128::MAX, u128::MAX / 5), u128::MAX / 5);
+        let a = factorial_bigint(30);
+        let b = factorial_bigint(20) * 23u32 * 29u32 * 31u32;
+        assert_eq!(gcd(a, b), factorial_bigint(20) * 23u32 * 29u32);
+        assert_eq!(gcd(BigInt::from(-4), BigInt::from(6)), BigInt::from(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn gcd_overflow_test() {
+        gcd(i64::MIN, i64::MIN);
+    }
+
+    #[test]
+    fn lcm_test() {
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(-4, 6), 12);
+        assert_eq!(lcm(4, -6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        assert_eq!(lcm(0, 0), 0);
+        assert_eq!(lcm(1u8, 255), 255);
+        assert_eq!((1..=20u64).fold(1, lcm), 232792560);
+        assert_eq!(
+            lcm(2u128.pow(63) - 25, 2u128.pow(62) + 1),
+            42535295865117307826853047505141104615
+        );
+        assert_eq!(
+            lcm(BigInt::from(u64::MAX), BigInt::from(u64::MAX - 1)),
+            BigInt::from(u64::MAX as u128 * (u64::MAX - 1) as u128)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn lcm_overflow_test() {
+        lcm(200u8, 3);
+    }
+
+    #[test]
+    fn extended_gcd_test() {
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(extended_gcd(0, 0), (0, 1, 0));
+        assert_eq!(extended_gcd(-5, 0), (5, -1, 0));
+        for a in -60i64..60 {
+            for b in -60i64..60 {
+                let (g, x, y) = extended_gcd(a, b);
+                assert_eq!(g, gcd(a, b));
+                assert_eq!(a * x + b * y, g, "a = {}, b = {}", a, b);
+            }
+        }
+        let (a, b) = (i64::MAX, i64::MAX - 1);
+        let (g, x, y) = extended_gcd(a, b);
+        assert_eq!(g, 1);
+        assert_eq!(a as i128 * x as i128 + b as i128 * y as i128, 1);
+        assert_eq!(extended_gcd(i64::MIN, -1), (1, 0, -1));
+        let (g, x, y) = extended_gcd(i8::MIN, 127);
+        assert_eq!(g, 1);
+        assert_eq!(-128 * x as i32 + 127 * y as i32, 1);
+        let (a, b): (BigInt, BigInt) = (factorial_bigint(40) + 1, factorial_bigint(30) - 1);
+        let (g, x, y) = extended_gcd(a.clone(), b.clone());
+        assert_eq!(g, gcd(a.clone(), b.clone()));
+        assert_eq!(a * x + b * y, g);
+    }
+
+    #[test]
+    fn mod_pow_test() {
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(2, 0, 7), 1);
+        assert_eq!(mod_pow(2, 0, 1), 0);
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+        assert_eq!(mod_pow(0u8, 0, 3), 1);
+        for base in -20i32..20 {
+            for exp in 0..10 {
+                for m in 1..30 {
+                    let expected = (base as i64).pow(exp as u32).rem_euclid(m as i64);
+                    assert_eq!(mod_pow(base, exp, m) as i64, expected);
+                }
+            }
+        }
+        assert_eq!(mod_pow(250u8, 250, 251), 1);
+        assert_eq!(mod_pow(u64::MAX - 1, u64::MAX, u64::MAX), u64::MAX - 1);
+        assert_eq!(
+            mod_pow(3u128, 2u128.pow(127) + 12345, u128::MAX),
+            120766279275203510067067447553244386478
+        );
+        // Fermat's little theorem, 2^127 - 1 is a prime
+        assert_eq!(mod_pow(2i128.pow(126) + 5, i128::MAX - 1, i128::MAX), 1);
+        assert_eq!(
+            mod_pow(-7i128, 12345678901234567890, 2i128.pow(126) + 3),
+            33073131340442459830503665714648659775
+        );
+        let m: BigInt = factorial_bigint(50) + 1;
+        let base = factorial_bigint(40);
+        let exp = factorial_bigint(25);
+        assert_eq!(
+            mod_pow(base.clone(), exp.clone(), m.clone()),
+            base.modpow(&exp, &m)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn mod_pow_negative_exponent_test() {
+        mod_pow(2, -1, 5);
+    }
+
+    #[test]
+    fn mod_inverse_test() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(0, 1), Some(0));
+        assert_eq!(mod_inverse(5, 1), Some(0));
+        for m in 1u32..80 {
+            for a in 0..m {
+                let expected = (0..m).find(|&x| (a * x) % m == 1 % m);
+                assert_eq!(mod_inverse(a, m), expected, "a = {}, m = {}", a, m);
+            }
+        }
+        assert_eq!(mod_inverse(2u8, 255), Some(128));
+        assert_eq!(mod_inverse(u128::MAX - 1, u128::MAX), Some(u128::MAX - 1));
+        assert_eq!(
+            mod_inverse(12345678901234567890123i128, i128::MAX),
+            Some(89721932055196168212235709152917502039)
+        );
+        let m: BigInt = factorial_bigint(50) + 1;
+        let inverse = mod_inverse(factorial_bigint(30), m.clone()).unwrap();
+        assert_eq!(factorial_bigint(30) * inverse % m, BigInt::from(1));
+    }
+
+    #[test]
+    fn crt_test() {
+        assert_eq!(crt::<i32>(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 3), (-2, 5)]), Some((8, 15)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        for m1 in 1u32..16 {
+            for m2 in 1u32..16 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let m = lcm(m1, m2);
+                        let expected = (0..m)
+                            .find(|x| x % m1 == r1 && x % m2 == r2)
+                            .map(|x| (x, m));
+                        assert_eq!(crt(&[(r1, m1), (r2, m2)]), expected);
+                    }
+                }
+            }
+        }
+        let primes = [4294967291u64, 4294967279, 4294967231];
+        let (x, m) = crt(&[
+            (1u128, primes[0] as u128),
+            (2, primes[1] as u128),
+            (3, primes[2] as u128),
+        ])
+        .unwrap();
+        assert_eq!(m, primes.iter().map(|&p| p as u128).product());
+        for (i, &p) in primes.iter().enumerate() {
+            assert_eq!(x % p as u128, i as u128 + 1);
+        }
+        let (x, m) = crt(&[(200u8, 251), (1, 1)]).unwrap();
+        assert_eq!((x, m), (200, 251));
+    }
+
+    #[test]
+    #[should_panic]
+    fn crt_overflow_test() {
+        crt(&[(1u8, 16), (2, 17)]);
+    }
+
+    #[test]
+    fn isqrt_test() {
+        for n in 0u64..10_000 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "n = {}", n);
+        }
+        assert_eq!(isqrt(u8::MAX), 15);
+        assert_eq!(isqrt(i8::MAX), 11);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt(i128::MAX), 13043817825332782212);
+        assert_eq!(isqrt(BigInt::from(10).pow(100)), BigInt::from(10).pow(50));
+        assert_eq!(
+            isqrt(BigInt::from(10).pow(100) - 1),
+            BigInt::from(10).pow(50) - 1
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn isqrt_negative_test() {
+        isqrt(-1);
+    }
+
+    #[test]
+    fn abundance_test() {
+        assert_eq!(abundance(1), Abundance::Deficient);
+        assert_eq!(abundance(6), Abundance::Perfect);
+        assert_eq!(abundance(12), Abundance::Abundant);
+        assert_eq!(abundance(945), Abundance::Abundant);
+        assert_eq!(abundance(33550336), Abundance::Perfect);
+        assert_eq!(abundance(8589869056), Abundance::Perfect);
+        let abundant = (1..1000)
+            .filter(|&n| abundance(n) == Abundance::Abundant)
+            .count();
+        assert_eq!(abundant, 245);
+        assert!(!is_perfect(0));
+        assert!(is_perfect(137438691328));
+        assert!(!is_perfect(137438691329));
+    }
+
     #[test]
     fn get_collatz_sequence_test() {
         assert_eq!(get_collatz_sequence(1), [1]);
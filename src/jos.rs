@@ -66,7 +66,7 @@ mod tests {
 
     #[test]
     fn get_operating_system_name_test() {
-        assert!(get_operating_system_name().len() > 0);
+        assert!(!get_operating_system_name().is_empty());
     }
 
     #[test]
@@ -12,6 +12,8 @@
 //! 3. Swap `a[k]` with `a[l]`.
 //! 4. Reverse the sequence from `a[k + 1]` up to end including the final element.
 
+use std::cmp::Ordering;
+
 /// Generates the lexicographically next permutation of an array / vector.
 ///
 /// Returns `false` if the permutation is the last permutation.
@@ -42,7 +44,7 @@ pub fn lexicographically_next_permutation<T: PartialOrd + Copy>(a: &mut [T]) ->
     }
     // else, if a[i] < a[i + 1]
     let mut j = a.len() - 1;
-    while !(a[j] > a[i]) {
+    while a[j].partial_cmp(&a[i]) != Some(Ordering::Greater) {
         j -= 1;
     }
     (a[i], a[j]) = (a[j], a[i]); // swap
@@ -4,18 +4,38 @@
 //!
 //! - call an external command and get its exit code, stdout, and stderr
 //! - call an external command (and see its output on the stdout)
+//! - build a command with its working directory, environment, stdin and a timeout
+//! - run a command in the background and keep a handle to it
+//! - stream the output lines of a long-running command as they arrive
+//! - run a list of commands in parallel
+//! - run a pipeline of commands with redirections (like `ls | grep foo > out.txt`)
 
 use shlex;
 
-use std::process;
+use crate::jos;
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 /// Stores process information: exit code, stdout, stderr.
+///
+/// `timed_out` is `true` if the process was killed because it
+/// ran longer than its timeout (see [`Cmd::timeout`]).
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct ProcStat {
     pub exit_code: i32,
     pub stdout: String,
     pub stderr: String,
+    pub timed_out: bool,
 }
 
 impl ProcStat {
@@ -32,6 +52,58 @@ impl ProcStat {
     }
 }
 
+/// The errors that can happen when running an external command.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The command line cannot be parsed (e.g. unbalanced quotes, empty command).
+    Parse(String),
+    /// The program was not found.
+    NotFound(String),
+    /// The program cannot be executed because of missing permissions.
+    PermissionDenied(String),
+    /// The process was terminated by the given signal.
+    Signal(i32),
+    /// The process didn't complete within its timeout (and it was killed).
+    Timeout,
+    /// Any other I/O error.
+    Io(io::Error),
+    /// The command was not started because an earlier command failed
+    /// (see [`Parallel::fail_fast`]).
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(msg) => write!(f, "{}", msg),
+            Error::NotFound(program) => write!(f, "command {:?} was not found", program),
+            Error::PermissionDenied(program) => {
+                write!(f, "permission denied to execute {:?}", program)
+            }
+            Error::Signal(signal) => write!(f, "process terminated by signal {}", signal),
+            Error::Timeout => write!(f, "process timed out"),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Cancelled => write!(f, "command was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
 /// Executes an external command and gets its exit code, stdout and stderr.
 ///
 /// It waits for the command to complete.
@@ -39,7 +111,11 @@ impl ProcStat {
 /// The three values are returned in a `ProcStat` structure.
 ///
 /// The command must be a simple command with some optional arguments.
-/// Pipes, redirections are not allowed.
+/// Pipes, redirections are not allowed (see [`run_pipeline`] for that).
+///
+/// It panics if the command cannot be parsed or started, and it returns `None`
+/// if the process was terminated by a signal. If you prefer a `Result`,
+/// use [`try_get_exitcode_stdout_stderr`].
 ///
 /// # Examples
 ///
@@ -63,28 +139,40 @@ impl ProcStat {
 /// # Sample Output
 ///
 /// ```text
-/// ProcStat { exit_code: 0, stdout: "Hello Rust!\n", stderr: "" }
-/// ProcStat { exit_code: 0, stdout: "Python 3.10.5\n", stderr: "" }
+/// ProcStat { exit_code: 0, stdout: "Hello Rust!\n", stderr: "", timed_out: false }
+/// ProcStat { exit_code: 0, stdout: "Python 3.10.5\n", stderr: "", timed_out: false }
 /// "rustc 1.62.1 (e092d0b6b 2022-07-16)"
 /// ```
 pub fn get_exitcode_stdout_stderr(cmd: &str) -> Option<ProcStat> {
-    let parts = shlex::split(cmd).unwrap_or_else(|| panic!("cannot parse command {:?}", cmd));
-    let head = &parts[0];
-    let tail = &parts[1..];
-
-    let mut p = process::Command::new(head);
-    p.args(tail);
-    let p = p
-        .output()
-        .unwrap_or_else(|_| panic!("failed to execute {:?}", cmd));
-
-    let result = ProcStat {
-        exit_code: p.status.code()?,
-        stdout: String::from_utf8_lossy(&p.stdout).to_string(),
-        stderr: String::from_utf8_lossy(&p.stderr).to_string(),
-    };
+    match try_get_exitcode_stdout_stderr(cmd) {
+        Ok(stat) => Some(stat),
+        Err(Error::Signal(_)) => None,
+        Err(e) => panic!("failed to execute {:?}: {}", cmd, e),
+    }
+}
 
-    Some(result)
+/// Executes an external command and gets its exit code, stdout and stderr.
+///
+/// Like [`get_exitcode_stdout_stderr`], but it never panics. Errors
+/// (parse error, command not found, termination by a signal, etc.)
+/// are returned as an [`Error`].
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::{self, Error};
+///
+/// let stat = jprocess::try_get_exitcode_stdout_stderr("rustc --version").unwrap();
+/// assert_eq!(stat.exit_code, 0);
+///
+/// let result = jprocess::try_get_exitcode_stdout_stderr("nothing20261018 --version");
+/// assert!(matches!(result, Err(Error::NotFound(_))));
+///
+/// let result = jprocess::try_get_exitcode_stdout_stderr("echo 'unbalanced");
+/// assert!(matches!(result, Err(Error::Parse(_))));
+/// ```
+pub fn try_get_exitcode_stdout_stderr(cmd: &str) -> Result<ProcStat, Error> {
+    Cmd::parse(cmd)?.output()
 }
 
 /// Executes an external command and waits for it to complete.
@@ -93,7 +181,10 @@ pub fn get_exitcode_stdout_stderr(cmd: &str) -> Option<ProcStat> {
 /// Similar to Python's `os.system("something")`.
 ///
 /// The command must be a simple command with some optional arguments.
-/// Pipes, redirections are not allowed.
+/// Pipes, redirections are not allowed (see [`run_pipeline`] for that).
+///
+/// It panics if the command cannot be parsed or started. If you need the exit code,
+/// or you prefer a `Result`, use [`try_exec_cmd`].
 ///
 /// # Examples
 ///
@@ -108,16 +199,26 @@ pub fn get_exitcode_stdout_stderr(cmd: &str) -> Option<ProcStat> {
 /// rustc 1.62.1 (e092d0b6b 2022-07-16)
 /// ```
 pub fn exec_cmd(cmd: &str) {
-    let parts = shlex::split(cmd).unwrap();
-    let head = &parts[0];
-    let tail = &parts[1..];
+    match try_exec_cmd(cmd) {
+        Ok(_) | Err(Error::Signal(_)) => (),
+        Err(e) => panic!("command {:?} failed to start: {}", cmd, e),
+    }
+}
 
-    let mut p = process::Command::new(head);
-    p.args(tail);
-    let mut child = p
-        .spawn()
-        .unwrap_or_else(|_| panic!("command {:?} failed to start", cmd));
-    child.wait().expect("command wasn't running");
+/// Executes an external command, waits for it to complete and returns its exit code.
+///
+/// Like [`exec_cmd`], but it never panics. Errors are returned as an [`Error`].
+///
+/// # Examples
+///
+/// ```
+/// let exit_code = jabba_lib::jprocess::try_exec_cmd("rustc --version").unwrap();
+/// assert_eq!(exit_code, 0);
+///
+/// assert!(jabba_lib::jprocess::try_exec_cmd("nothing20261018").is_err());
+/// ```
+pub fn try_exec_cmd(cmd: &str) -> Result<i32, Error> {
+    Cmd::parse(cmd)?.exec()
 }
 
 /// Executes an external command in the background (i.e., it doesn't wait for it to complete).
@@ -126,13 +227,22 @@ pub fn exec_cmd(cmd: &str) {
 /// Similar to Python's `os.system("something &")`.
 ///
 /// The command must be a simple command with some optional arguments.
-/// Pipes, redirections are not allowed.
+/// Pipes, redirections are not allowed (see [`run_pipeline`] for that).
+///
+/// It returns a [`Job`] handle that you can use to wait for the command, kill it,
+/// or check if it's still running. You can also ignore it.
+///
+/// It panics if the command cannot be parsed or started. If you prefer
+/// a `Result`, use [`try_exec_cmd_in_bg`].
 ///
 /// # Examples
 ///
 /// ```
 /// let cmd = "rustc --version";
-/// jabba_lib::jprocess::exec_cmd_in_bg(cmd);
+/// let job = jabba_lib::jprocess::exec_cmd_in_bg(cmd);
+/// // do something else in the meantime...
+/// let stat = job.wait().unwrap();
+/// assert_eq!(stat.exit_code, 0);
 /// ```
 ///
 /// # Sample Output
@@ -140,94 +250,2173 @@ pub fn exec_cmd(cmd: &str) {
 /// ```text
 /// rustc 1.62.1 (e092d0b6b 2022-07-16)
 /// ```
-pub fn exec_cmd_in_bg(cmd: &str) {
-    let parts = shlex::split(cmd).unwrap();
-    let head = &parts[0];
-    let tail = &parts[1..];
+pub fn exec_cmd_in_bg(cmd: &str) -> Job {
+    try_exec_cmd_in_bg(cmd).unwrap_or_else(|e| panic!("command {:?} failed to start: {}", cmd, e))
+}
 
-    process::Command::new(head)
-        .args(tail)
-        .spawn()
-        .unwrap_or_else(|_| panic!("command {:?} failed to start", cmd));
+/// Executes an external command in the background (i.e., it doesn't wait for it to complete).
+///
+/// Like [`exec_cmd_in_bg`], but it never panics. Errors are returned as an [`Error`].
+///
+/// # Examples
+///
+/// ```
+/// assert!(jabba_lib::jprocess::try_exec_cmd_in_bg("rustc --version").is_ok());
+/// assert!(jabba_lib::jprocess::try_exec_cmd_in_bg("nothing20261018").is_err());
+/// ```
+pub fn try_exec_cmd_in_bg(cmd: &str) -> Result<Job, Error> {
+    Cmd::parse(cmd)?.spawn()
 }
 
-// ==========================================================================
+/////////////////////
+// Command builder //
+/////////////////////
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// A builder for running an external command.
+///
+/// Unlike [`get_exitcode_stdout_stderr`], the arguments are given one by one
+/// (no parsing is involved), and you can set the working directory, the environment
+/// variables, the stdin and a timeout.
+///
+/// A command that runs longer than its timeout is killed. In this case
+/// the `timed_out` field of the returned `ProcStat` is `true`.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::Cmd;
+///
+/// let stat = Cmd::new("tr")
+///     .arg("a-z")
+///     .arg("A-Z")
+///     .stdin_str("hello")
+///     .timeout(5.0)
+///     .output()
+///     .unwrap();
+///
+/// assert_eq!(stat.exit_code, 0);
+/// assert_eq!(stat.stdout, "HELLO");
+/// assert_eq!(stat.timed_out, false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cmd {
+    program: String,
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+    envs: Vec<(String, Option<String>)>, // None: remove the variable
+    stdin: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    capture: bool,
+}
 
-    #[test]
-    fn get_exitcode_stdout_stderr_exit_code_test() {
-        use which::which;
+impl Cmd {
+    /// Creates a new command that will run the given program.
+    pub fn new(program: &str) -> Cmd {
+        Cmd {
+            program: program.to_string(),
+            args: vec![],
+            cwd: None,
+            envs: vec![],
+            stdin: None,
+            timeout: None,
+            capture: false,
+        }
+    }
 
-        let cmd = "rustc";
-        let result = which(cmd);
-        if result.is_err() {
-            return;
+    /// Creates a new command by parsing a command line.
+    ///
+    /// The command must be a simple command with some optional arguments.
+    /// Pipes, redirections are not allowed (see [`run_pipeline`] for that).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jabba_lib::jprocess::Cmd;
+    ///
+    /// let stat = Cmd::parse("rustc --version").unwrap().output().unwrap();
+    /// assert!(stat.stdout.starts_with("rustc"));
+    ///
+    /// assert!(Cmd::parse("echo 'unbalanced").is_err());
+    /// ```
+    pub fn parse(cmd: &str) -> Result<Cmd, Error> {
+        let parts = shlex::split(cmd).ok_or_else(|| parse_error(cmd, "invalid quoting"))?;
+        match parts.split_first() {
+            Some((program, args)) => Ok(Cmd::new(program).args(args)),
+            None => Err(parse_error(cmd, "missing command")),
         }
-        // else, if "rustc" is available (should be...)
-        let cmd = "rustc --version";
-        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
-        assert_eq!(stat.exit_code, 0);
     }
 
-    #[test]
-    fn get_exitcode_stdout_stderr_stdout_test() {
-        use which::which;
+    /// Adds an argument.
+    pub fn arg<S: AsRef<str>>(mut self, arg: S) -> Cmd {
+        self.args.push(arg.as_ref().to_string());
+        self
+    }
 
-        let cmd = "rustc";
-        let result = which(cmd);
-        if result.is_err() {
-            return;
+    /// Adds several arguments.
+    pub fn args<I, S>(mut self, args: I) -> Cmd
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    /// Sets the working directory of the command.
+    pub fn cwd<P: AsRef<Path>>(mut self, dir: P) -> Cmd {
+        self.cwd = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets an environment variable for the command.
+    ///
+    /// The other variables are inherited.
+    pub fn env(mut self, key: &str, value: &str) -> Cmd {
+        self.envs.push((key.to_string(), Some(value.to_string())));
+        self
+    }
+
+    /// Removes an environment variable from the inherited environment.
+    pub fn env_remove(mut self, key: &str) -> Cmd {
+        self.envs.push((key.to_string(), None));
+        self
+    }
+
+    /// The given text is fed to the stdin of the command.
+    ///
+    /// Without it, the command gets an empty stdin.
+    pub fn stdin_str(self, text: &str) -> Cmd {
+        self.stdin_bytes(text.as_bytes())
+    }
+
+    /// The given bytes are fed to the stdin of the command.
+    pub fn stdin_bytes(mut self, data: &[u8]) -> Cmd {
+        self.stdin = Some(data.to_vec());
+        self
+    }
+
+    /// Sets a timeout (in seconds, given as a float).
+    ///
+    /// If the command is still running when the timeout expires, it's killed.
+    pub fn timeout(mut self, secs: f64) -> Cmd {
+        self.timeout = Some(Duration::from_secs_f64(secs));
+        self
+    }
+
+    /// The stdout and stderr of the background job are captured
+    /// (instead of going to the stdout / stderr).
+    ///
+    /// Only used by [`Cmd::spawn`]; the other methods know on their own
+    /// whether they capture the output.
+    pub fn capture(mut self) -> Cmd {
+        self.capture = true;
+        self
+    }
+
+    /// Executes the command, waits for it to complete (or to time out),
+    /// and gets its exit code, stdout and stderr.
+    ///
+    /// If the process was terminated by a signal (and not because of the timeout),
+    /// [`Error::Signal`] is returned.
+    pub fn output(&self) -> Result<ProcStat, Error> {
+        let (mut child, feeder) = self.spawn_with(true, Stdio::null)?;
+        let stdout = child.stdout.take().map(Capture::start);
+        let stderr = child.stderr.take().map(Capture::start);
+
+        let (status, timed_out) = wait_with_timeout(&mut child, self.timeout)?;
+        if timed_out {
+            // a process started by the command may still keep the pipes open
+            return Ok(ProcStat {
+                exit_code: exit_code_of(status),
+                stdout: finish_capture_within(stdout, GRACE_PERIOD),
+                stderr: finish_capture_within(stderr, GRACE_PERIOD),
+                timed_out,
+            });
         }
-        // else, if "rustc" is available (should be...)
-        let cmd = "rustc --version";
-        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
-        assert!(stat.stdout.starts_with("rustc"));
+        if let Some(feeder) = feeder {
+            let _ = feeder.join();
+        }
+        if let Some(signal) = signal_of(status) {
+            return Err(Error::Signal(signal));
+        }
+
+        Ok(ProcStat {
+            exit_code: exit_code_of(status),
+            stdout: finish_capture(stdout),
+            stderr: finish_capture(stderr),
+            timed_out,
+        })
     }
 
-    #[test]
-    fn get_exitcode_stdout_stderr_stderr_test() {
-        use which::which;
+    /// Executes the command, waits for it to complete and returns its exit code.
+    ///
+    /// The command's stdin, stdout and stderr are inherited, i.e. not captured
+    /// (unless you set a stdin with [`Cmd::stdin_str`]).
+    ///
+    /// If the command runs longer than its timeout, it's killed and
+    /// [`Error::Timeout`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jabba_lib::jprocess::{Cmd, Error};
+    ///
+    /// let exit_code = Cmd::new("rustc").arg("--version").exec().unwrap();
+    /// assert_eq!(exit_code, 0);
+    ///
+    /// let result = Cmd::new("sleep").arg("10").timeout(0.1).exec();
+    /// assert!(matches!(result, Err(Error::Timeout)));
+    /// ```
+    pub fn exec(&self) -> Result<i32, Error> {
+        let (mut child, _) = self.spawn_with(false, Stdio::inherit)?;
+        let (status, timed_out) = wait_with_timeout(&mut child, self.timeout)?;
+        if timed_out {
+            return Err(Error::Timeout);
+        }
+        if let Some(signal) = signal_of(status) {
+            return Err(Error::Signal(signal));
+        }
 
-        let cmd = "rustc";
-        let result = which(cmd);
-        if result.is_err() {
-            return;
+        Ok(exit_code_of(status))
+    }
+
+    /// Starts the command in the background and returns a [`Job`] handle.
+    ///
+    /// The stdin, stdout and stderr are inherited, unless you set a stdin
+    /// with [`Cmd::stdin_str`] or ask for capturing the output with [`Cmd::capture`].
+    /// The timeout (if any) is checked by [`Job::wait`] and [`Job::try_wait`].
+    pub fn spawn(&self) -> Result<Job, Error> {
+        reap_orphans();
+        let (mut child, _) = self.spawn_with(self.capture, Stdio::inherit)?;
+        let stdout = child.stdout.take().map(Capture::start);
+        let stderr = child.stderr.take().map(Capture::start);
+
+        Ok(Job {
+            child: Some(child),
+            stdout,
+            stderr,
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            reaped: false,
+            timed_out: false,
+        })
+    }
+
+    /// Spawns the command and starts feeding its stdin (if a stdin was set).
+    ///
+    /// If `capture` is `true`, stdout and stderr are piped. If no stdin was set,
+    /// the stdin is `default_stdin`.
+    fn spawn_with(
+        &self,
+        capture: bool,
+        default_stdin: fn() -> Stdio,
+    ) -> Result<(process::Child, Option<JoinHandle<()>>), Error> {
+        let mut p = self.command();
+        if capture {
+            p.stdout(Stdio::piped()).stderr(Stdio::piped());
         }
-        // else, if "rustc" is available (should be...)
-        let cmd = "rustc --nothing20220731"; // this option doesn't exist
-        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
-        assert!(stat.exit_code != 0);
-        assert!(stat.stderr.len() > 0);
+        p.stdin(if self.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            default_stdin()
+        });
+        let mut child = p.spawn().map_err(|e| spawn_error(&self.program, e))?;
+
+        // feed the stdin in a thread, otherwise a command that writes
+        // lots of output before reading its input could block us
+        let feeder = match (child.stdin.take(), &self.stdin) {
+            (Some(mut stdin), Some(data)) => {
+                let data = data.clone();
+                Some(thread::spawn(move || {
+                    let _ = stdin.write_all(&data);
+                }))
+            }
+            _ => None,
+        };
+
+        Ok((child, feeder))
     }
 
-    #[test]
-    fn trimmed_output_test() {
-        use which::which;
+    fn command(&self) -> process::Command {
+        let mut p = process::Command::new(&self.program);
+        p.args(&self.args);
+        if let Some(dir) = &self.cwd {
+            p.current_dir(dir);
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => p.env(key, value),
+                None => p.env_remove(key),
+            };
+        }
+        p
+    }
+}
 
-        let cmd = "rustc";
-        let result = which(cmd);
-        if result.is_err() {
-            return;
+/////////////////////
+// Background jobs //
+/////////////////////
+
+/// Background jobs that were dropped while still running.
+/// They are reaped when a new job is started (like Python's `subprocess` does).
+static ORPHANS: Mutex<Vec<process::Child>> = Mutex::new(Vec::new());
+
+/// A handle to a command running in the background.
+///
+/// Created by [`exec_cmd_in_bg`] or [`Cmd::spawn`].
+///
+/// If a `Job` is dropped while its process is still running, the process
+/// is not killed. It'll be reaped later, thus it doesn't stay a zombie.
+///
+/// If the job runs longer than its timeout, it's killed, and from then on
+/// both [`Job::try_wait`] and [`Job::wait`] return [`Error::Timeout`].
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::Cmd;
+///
+/// let mut job = Cmd::new("rustc").arg("--version").capture().spawn().unwrap();
+/// println!("started with PID {}", job.pid());
+/// // do something else in the meantime...
+/// let stat = job.wait().unwrap();
+/// assert_eq!(stat.exit_code, 0);
+/// assert!(stat.stdout.starts_with("rustc"));
+/// ```
+#[derive(Debug)]
+pub struct Job {
+    child: Option<process::Child>, // it's only None after drop()
+    stdout: Option<Capture>,
+    stderr: Option<Capture>,
+    deadline: Option<Instant>,
+    reaped: bool,
+    timed_out: bool,
+}
+
+impl Job {
+    /// Returns the OS-assigned process identifier of the job.
+    pub fn pid(&self) -> u32 {
+        self.child.as_ref().map_or(0, |child| child.id())
+    }
+
+    /// Returns `true` if the job is still running.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.try_wait(), Ok(None))
+    }
+
+    /// Checks if the job has completed, without blocking.
+    ///
+    /// Returns `None` if the job is still running, or its exit code otherwise.
+    /// If the job has run longer than its timeout, it's killed and
+    /// [`Error::Timeout`] is returned.
+    pub fn try_wait(&mut self) -> Result<Option<i32>, Error> {
+        if self.timed_out {
+            return Err(Error::Timeout);
+        }
+        let deadline = self.deadline;
+        let child = self.child_mut();
+        let status = match child.try_wait()? {
+            Some(status) => status,
+            None if deadline.is_some_and(|deadline| Instant::now() >= deadline) => {
+                let _ = child.kill();
+                child.wait()?;
+                self.reaped = true;
+                self.timed_out = true;
+                return Err(Error::Timeout);
+            }
+            None => return Ok(None),
+        };
+        self.reaped = true;
+        match signal_of(status) {
+            Some(signal) => Err(Error::Signal(signal)),
+            None => Ok(Some(exit_code_of(status))),
         }
-        // else, if "rustc" is available (should be...)
-        let cmd = "rustc --version";
-        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
-        assert!(stat.output() == stat.stdout.clone());
-        assert!(stat.trimmed_output().len() < stat.output().len());
     }
 
-    #[test]
-    fn exec_cmd_test() {
-        let cmd = "rustc --version";
-        exec_cmd(cmd);
+    /// Waits for the job to complete (or to time out), and gets its exit code,
+    /// stdout and stderr.
+    ///
+    /// The stdout and stderr are empty if the output was not captured
+    /// (see [`Cmd::capture`]).
+    ///
+    /// If the job has run longer than its timeout (now or in an earlier
+    /// [`Job::try_wait`]), it's killed and [`Error::Timeout`] is returned.
+    /// If the process was terminated by a signal (and not because of the timeout),
+    /// [`Error::Signal`] is returned.
+    pub fn wait(mut self) -> Result<ProcStat, Error> {
+        if self.timed_out {
+            return Err(Error::Timeout);
+        }
+        let timeout = self
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let (status, timed_out) = wait_with_timeout(self.child_mut(), timeout)?;
+        self.reaped = true;
+        if timed_out {
+            self.timed_out = true;
+            return Err(Error::Timeout);
+        }
+        if let Some(signal) = signal_of(status) {
+            return Err(Error::Signal(signal));
+        }
+
+        Ok(ProcStat {
+            exit_code: exit_code_of(status),
+            stdout: finish_capture(self.stdout.take()),
+            stderr: finish_capture(self.stderr.take()),
+            timed_out,
+        })
     }
 
-    #[test]
-    fn exec_cmd_in_bg_test() {
-        let cmd = "rustc --version";
-        exec_cmd_in_bg(cmd);
+    /// Kills the job (with SIGKILL under Linux).
+    ///
+    /// It doesn't wait for the process to exit; call [`Job::wait`] for that.
+    pub fn kill(&mut self) -> Result<(), Error> {
+        if !self.reaped {
+            self.child_mut().kill()?;
+        }
+        Ok(())
+    }
+
+    fn child_mut(&mut self) -> &mut process::Child {
+        self.child
+            .as_mut()
+            .expect("the child is only taken in drop()")
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            if !self.reaped && matches!(child.try_wait(), Ok(None)) {
+                ORPHANS.lock().unwrap().push(child);
+            }
+        }
+    }
+}
+
+/// Waits for the dropped jobs that have completed in the meantime.
+fn reap_orphans() {
+    let mut orphans = ORPHANS.lock().unwrap();
+    orphans.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+}
+
+/// A small job table that keeps track of background jobs.
+///
+/// Jobs get an ID (starting from 1, like `%1` in a shell). Call [`JobTable::reap`]
+/// from time to time to collect the finished jobs.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::{Cmd, JobTable};
+///
+/// let mut table = JobTable::new();
+/// let id1 = table.add(Cmd::new("rustc").arg("--version").spawn().unwrap());
+/// let id2 = table.add(Cmd::new("rustc").arg("--help").capture().spawn().unwrap());
+/// assert_eq!(table.len(), 2);
+///
+/// let finished = table.wait_all();
+/// assert_eq!(finished.len(), 2);
+/// assert_eq!(finished[0].0, id1);
+/// assert_eq!(finished[1].0, id2);
+/// assert!(table.is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct JobTable {
+    jobs: Vec<(usize, Job)>,
+    last_id: usize,
+}
+
+impl JobTable {
+    /// Creates an empty job table.
+    pub fn new() -> JobTable {
+        JobTable::default()
+    }
+
+    /// Adds a job to the table and returns its ID.
+    pub fn add(&mut self, job: Job) -> usize {
+        self.last_id += 1;
+        self.jobs.push((self.last_id, job));
+        self.last_id
+    }
+
+    /// Returns the job with the given ID (if it's still in the table).
+    pub fn get(&mut self, id: usize) -> Option<&mut Job> {
+        self.jobs
+            .iter_mut()
+            .find(|(job_id, _)| *job_id == id)
+            .map(|(_, job)| job)
+    }
+
+    /// Returns the number of jobs in the table.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Returns `true` if the table contains no jobs.
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Returns the IDs of the jobs in the table.
+    pub fn ids(&self) -> Vec<usize> {
+        self.jobs.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Removes the finished jobs from the table and returns their results
+    /// (with their IDs). It doesn't block.
+    pub fn reap(&mut self) -> Vec<(usize, Result<ProcStat, Error>)> {
+        let mut finished = vec![];
+        let mut i = 0;
+        while i < self.jobs.len() {
+            if self.jobs[i].1.is_running() {
+                i += 1;
+            } else {
+                let (id, job) = self.jobs.remove(i);
+                finished.push((id, job.wait()));
+            }
+        }
+        finished
+    }
+
+    /// Waits for all the jobs, removes them from the table and returns their results
+    /// (with their IDs).
+    pub fn wait_all(&mut self) -> Vec<(usize, Result<ProcStat, Error>)> {
+        self.jobs
+            .drain(..)
+            .map(|(id, job)| (id, job.wait()))
+            .collect()
+    }
+
+    /// Kills all the jobs in the table. The killed jobs remain in the table until reaped.
+    pub fn kill_all(&mut self) {
+        for (_, job) in self.jobs.iter_mut() {
+            let _ = job.kill();
+        }
+    }
+}
+
+//////////////////////
+// Streaming output //
+//////////////////////
+
+/// Tells which output stream a line comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// An iterator over the output lines of a running command.
+///
+/// It yields the stdout and stderr lines as they arrive, tagged by [`Stream`].
+/// The lines don't contain the line terminator (`\n` or `\r\n`).
+/// Both pipes are read in background threads, thus a command that fills up
+/// one of its pipes can't block.
+///
+/// When the iteration is over, call [`OutputLines::finish`] to get the exit code and
+/// the complete output. If it's dropped before the command completes,
+/// the command is killed.
+///
+/// Created by [`Cmd::lines`].
+#[derive(Debug)]
+pub struct OutputLines {
+    child: process::Child,
+    feeder: Option<JoinHandle<()>>,
+    receiver: mpsc::Receiver<(Stream, Vec<u8>)>,
+    deadline: Option<Instant>,
+    timed_out: bool,
+    done: bool,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl OutputLines {
+    /// Reads the rest of the output, waits for the command to complete
+    /// and returns its exit code and its complete output.
+    ///
+    /// If the process was terminated by a signal (and not because of the timeout),
+    /// [`Error::Signal`] is returned.
+    pub fn finish(mut self) -> Result<ProcStat, Error> {
+        while self.next().is_some() {}
+        let status = self.child.wait()?;
+        self.done = true;
+        if !self.timed_out {
+            if let Some(feeder) = self.feeder.take() {
+                let _ = feeder.join();
+            }
+            if let Some(signal) = signal_of(status) {
+                return Err(Error::Signal(signal));
+            }
+        }
+
+        Ok(ProcStat {
+            exit_code: exit_code_of(status),
+            stdout: String::from_utf8_lossy(&self.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&self.stderr).to_string(),
+            timed_out: self.timed_out,
+        })
+    }
+
+    fn receive(&mut self) -> Option<(Stream, Vec<u8>)> {
+        loop {
+            let wait = match self.deadline {
+                _ if self.timed_out => GRACE_PERIOD, // the pipes may be kept open by a grandchild
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => return self.receiver.recv().ok(),
+            };
+            match self.receiver.recv_timeout(wait) {
+                Ok(message) => return Some(message),
+                Err(mpsc::RecvTimeoutError::Timeout) if !self.timed_out => {
+                    let _ = self.child.kill();
+                    self.timed_out = true;
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+impl Iterator for OutputLines {
+    type Item = (Stream, String);
+
+    fn next(&mut self) -> Option<(Stream, String)> {
+        let (stream, line) = self.receive()?;
+        match stream {
+            Stream::Stdout => self.stdout.extend_from_slice(&line),
+            Stream::Stderr => self.stderr.extend_from_slice(&line),
+        }
+        let line = String::from_utf8_lossy(&line);
+        let line = line.strip_suffix('\n').unwrap_or(&line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        Some((stream, line.to_string()))
+    }
+}
+
+impl Drop for OutputLines {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+impl Cmd {
+    /// Starts the command and returns an iterator over its output lines.
+    ///
+    /// The lines of stdout and stderr are yielded as they arrive, tagged by [`Stream`].
+    /// Useful for long-running commands whose output you want to show live.
+    /// The timeout of the command is respected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jabba_lib::jprocess::{Cmd, Stream};
+    ///
+    /// let mut lines = Cmd::new("ls").arg("-1").arg("src").lines().unwrap();
+    /// for (stream, line) in lines.by_ref() {
+    ///     if stream == Stream::Stdout {
+    ///         println!("{}", line);
+    ///     }
+    /// }
+    /// let stat = lines.finish().unwrap();
+    /// assert_eq!(stat.exit_code, 0);
+    /// assert!(stat.stdout.contains("lib.rs"));
+    /// ```
+    pub fn lines(&self) -> Result<OutputLines, Error> {
+        let (mut child, feeder) = self.spawn_with(true, Stdio::null)?;
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            send_lines(stdout, Stream::Stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            send_lines(stderr, Stream::Stderr, sender);
+        }
+
+        Ok(OutputLines {
+            child,
+            feeder,
+            receiver,
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            timed_out: false,
+            done: false,
+            stdout: vec![],
+            stderr: vec![],
+        })
+    }
+
+    /// Executes the command and calls `f` with each output line as it arrives.
+    ///
+    /// When the command completes, its exit code and its complete
+    /// output are returned (just like with [`Cmd::output`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jabba_lib::jprocess::{Cmd, Stream};
+    ///
+    /// let mut count = 0;
+    /// let stat = Cmd::new("ls")
+    ///     .arg("src")
+    ///     .stream(|stream, line| {
+    ///         if stream == Stream::Stdout {
+    ///             count += 1;
+    ///             println!("> {}", line);
+    ///         }
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(count, stat.stdout.lines().count());
+    /// ```
+    pub fn stream<F: FnMut(Stream, &str)>(&self, mut f: F) -> Result<ProcStat, Error> {
+        let mut lines = self.lines()?;
+        for (stream, line) in lines.by_ref() {
+            f(stream, &line);
+        }
+        lines.finish()
+    }
+}
+
+/// Executes an external command and calls `f` with each output line as it arrives.
+///
+/// The lines of stdout and stderr are tagged by [`Stream`]. When the command
+/// completes, its exit code and its complete output are returned.
+///
+/// The command must be a simple command with some optional arguments.
+/// Pipes, redirections are not allowed (see [`run_pipeline`] for that).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::{self, Stream};
+///
+/// let stat = jprocess::stream_cmd("rustc --version", |stream, line| match stream {
+///     Stream::Stdout => println!("{}", line),
+///     Stream::Stderr => eprintln!("{}", line),
+/// })
+/// .unwrap();
+/// assert_eq!(stat.exit_code, 0);
+/// ```
+pub fn stream_cmd<F: FnMut(Stream, &str)>(cmd: &str, f: F) -> Result<ProcStat, Error> {
+    Cmd::parse(cmd)?.stream(f)
+}
+
+/// Reads a pipe line by line in a background thread and sends the lines
+/// (with their line terminators) to the channel.
+fn send_lines<R: Read + Send + 'static>(
+    reader: R,
+    stream: Stream,
+    sender: mpsc::Sender<(Stream, Vec<u8>)>,
+) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        loop {
+            let mut line = vec![];
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if sender.send((stream, line)).is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+//////////////////////
+// Parallel running //
+//////////////////////
+
+/// Runs a list of commands in parallel, with at most N commands running at once.
+///
+/// By default, N is [`jos::get_available_parallelism`]. The results are returned
+/// in the order of the commands (not in the order of completion).
+///
+/// With fail-fast, no new command is started after a command has failed
+/// (i.e., it couldn't be started or it returned a non-zero exit code).
+/// The commands that were not started get [`Error::Cancelled`].
+/// The commands that are already running are not killed.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::Parallel;
+///
+/// let commands = ["rustc --version", "cargo --version", "rustc --help"];
+/// let results = Parallel::new(&commands)
+///     .max_jobs(2)
+///     .run_with_progress(|done, total| println!("{}/{} done", done, total));
+///
+/// assert_eq!(results.len(), 3);
+/// assert!(results[0].as_ref().unwrap().stdout.starts_with("rustc"));
+/// assert!(results[1].as_ref().unwrap().stdout.starts_with("cargo"));
+/// ```
+#[derive(Debug)]
+pub struct Parallel {
+    cmds: Vec<Result<Cmd, Error>>,
+    max_jobs: usize,
+    fail_fast: bool,
+}
+
+impl Parallel {
+    /// Creates a parallel runner for the given command lines.
+    ///
+    /// Each command must be a simple command with some optional arguments.
+    /// A command that cannot be parsed gets an [`Error::Parse`] in the results.
+    pub fn new<S: AsRef<str>>(cmds: &[S]) -> Parallel {
+        Parallel::from_cmds(cmds.iter().map(|cmd| Cmd::parse(cmd.as_ref())))
+    }
+
+    /// Creates a parallel runner for the given [`Cmd`]s.
+    ///
+    /// Useful if the commands need their own working directory, environment, timeout, etc.
+    pub fn from_cmds<I: IntoIterator<Item = Result<Cmd, Error>>>(cmds: I) -> Parallel {
+        Parallel {
+            cmds: cmds.into_iter().collect(),
+            max_jobs: jos::get_available_parallelism(),
+            fail_fast: false,
+        }
+    }
+
+    /// Sets the maximal number of commands that run at the same time.
+    ///
+    /// 0 is treated as 1.
+    pub fn max_jobs(mut self, n: usize) -> Parallel {
+        self.max_jobs = n.max(1);
+        self
+    }
+
+    /// If `true`, no new command is started after a command has failed.
+    pub fn fail_fast(mut self, value: bool) -> Parallel {
+        self.fail_fast = value;
+        self
+    }
+
+    /// Runs the commands and waits for all of them to complete.
+    ///
+    /// Returns a result for each command, in the order of the commands.
+    pub fn run(self) -> Vec<Result<ProcStat, Error>> {
+        self.run_with_progress(|_, _| ())
+    }
+
+    /// Like [`Parallel::run`], but `progress` is called after each completed command
+    /// with the number of completed commands and the total number of commands.
+    pub fn run_with_progress<F: FnMut(usize, usize)>(
+        self,
+        mut progress: F,
+    ) -> Vec<Result<ProcStat, Error>> {
+        let total = self.cmds.len();
+        let mut results: Vec<Option<Result<ProcStat, Error>>> = Vec::with_capacity(total);
+        let mut todo = vec![];
+        for (i, cmd) in self.cmds.into_iter().enumerate() {
+            match cmd {
+                Ok(cmd) => {
+                    todo.push((i, cmd));
+                    results.push(None);
+                }
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let stop = AtomicBool::new(self.fail_fast && todo.len() < total);
+        let next = AtomicUsize::new(0);
+        let mut done = total - todo.len();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..self.max_jobs.min(todo.len()) {
+                let sender = sender.clone();
+                let (todo, next, stop) = (&todo, &next, &stop);
+                let fail_fast = self.fail_fast;
+                scope.spawn(move || {
+                    while !stop.load(Ordering::SeqCst) {
+                        let k = next.fetch_add(1, Ordering::SeqCst);
+                        let Some((i, cmd)) = todo.get(k) else {
+                            break;
+                        };
+                        let result = cmd.output();
+                        // set before this worker could take the next command
+                        let failed = !matches!(&result, Ok(stat) if stat.exit_code == 0);
+                        if fail_fast && failed {
+                            stop.store(true, Ordering::SeqCst);
+                        }
+                        if sender.send((*i, result)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            // the results are collected (and the callback is called) in this thread
+            for (i, result) in receiver {
+                results[i] = Some(result);
+                done += 1;
+                progress(done, total);
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(Error::Cancelled)))
+            .collect()
+    }
+}
+
+/// Runs a list of commands in parallel and waits for all of them to complete.
+///
+/// At most [`jos::get_available_parallelism`] commands run at the same time.
+/// Returns a result for each command, in the order of the commands.
+/// For more options (concurrency limit, fail-fast, progress callback), see [`Parallel`].
+///
+/// # Examples
+///
+/// ```
+/// let commands = vec!["rustc --version", "cargo --version"];
+/// let results = jabba_lib::jprocess::run_parallel(&commands);
+///
+/// for result in results {
+///     println!("{}", result.unwrap().trimmed_output());
+/// }
+/// ```
+pub fn run_parallel<S: AsRef<str>>(cmds: &[S]) -> Vec<Result<ProcStat, Error>> {
+    Parallel::new(cmds).run()
+}
+
+///////////////
+// Pipelines //
+///////////////
+
+/// Stores the result of a pipeline: one `ProcStat` per stage and an overall exit code.
+///
+/// The exit code follows bash's `pipefail` option: it's the exit code of the
+/// last (rightmost) stage that failed, or 0 if every stage succeeded.
+///
+/// The stdout of a stage is only captured if it's the last stage and its output
+/// is not redirected to a file. Otherwise it's fed to the next stage (or to the file),
+/// thus the `stdout` field of that stage is empty.
+///
+/// If a stage was terminated by a signal, its exit code is 128 + the signal number
+/// (like in a shell).
+#[derive(Debug)]
+pub struct PipeStat {
+    pub exit_code: i32,
+    pub stages: Vec<ProcStat>,
+}
+
+impl PipeStat {
+    /// Returns a copy of the output of the last stage.
+    pub fn output(&self) -> String {
+        self.stages
+            .last()
+            .map(|stage| stage.output())
+            .unwrap_or_default()
+    }
+
+    /// Trims the trailing whitespaces from the output of the last stage.
+    pub fn trimmed_output(&self) -> String {
+        self.output().trim_end().to_string()
+    }
+}
+
+#[derive(Debug)]
+enum StderrTarget {
+    File(PathBuf, bool), // bool: append?
+    Stdout,
+}
+
+#[derive(Debug, Default)]
+struct Stage {
+    args: Vec<String>,
+    stdin: Option<PathBuf>,
+    stdout: Option<(PathBuf, bool)>, // bool: append?
+    stderr: Option<StderrTarget>,
+}
+
+/// A pipeline of external commands where the stdout of a command is
+/// connected to the stdin of the next one.
+///
+/// The stdout of the last command and the stderr of every command are captured
+/// (unless they are redirected to a file).
+///
+/// A pipeline can be built step by step, or it can be parsed from a string
+/// (see [`Pipeline::parse`]).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jprocess::Pipeline;
+///
+/// let stat = Pipeline::new()
+///     .pipe("echo hello world")
+///     .pipe("tr a-z A-Z")
+///     .run()
+///     .unwrap();
+///
+/// assert_eq!(stat.exit_code, 0);
+/// assert_eq!(stat.trimmed_output(), "HELLO WORLD");
+/// ```
+#[derive(Debug, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+    error: Option<String>,
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline.
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Parses a pipeline from a string.
+    ///
+    /// Supported operators: `|`, `<`, `>`, `>>`, `2>`, `2>>` and `2>&1`.
+    /// Operators inside quotes are taken literally. The words are split
+    /// with the same (shlex-based) rules as in [`get_exitcode_stdout_stderr`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let pipeline = jabba_lib::jprocess::Pipeline::parse("ls -al | grep 'a|b' > out.txt");
+    /// assert!(pipeline.is_ok());
+    ///
+    /// let pipeline = jabba_lib::jprocess::Pipeline::parse("ls -al |");
+    /// assert!(pipeline.is_err());
+    /// ```
+    pub fn parse(cmd: &str) -> Result<Pipeline, Error> {
+        let mut pipeline = Pipeline::new();
+        let mut stage = Stage::default();
+        let mut tokens = tokenize(cmd)?.into_iter();
+
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Word(word) => stage.args.push(word),
+                Token::Pipe => {
+                    if stage.args.is_empty() {
+                        return Err(parse_error(cmd, "missing command before '|'"));
+                    }
+                    pipeline.stages.push(mem::take(&mut stage));
+                }
+                Token::StderrToStdout => stage.stderr = Some(StderrTarget::Stdout),
+                op => {
+                    let path = match tokens.next() {
+                        Some(Token::Word(word)) => PathBuf::from(word),
+                        _ => return Err(parse_error(cmd, "missing file name after redirection")),
+                    };
+                    match op {
+                        Token::StdinFrom => stage.stdin = Some(path),
+                        Token::StdoutTo => stage.stdout = Some((path, false)),
+                        Token::StdoutAppend => stage.stdout = Some((path, true)),
+                        Token::StderrTo => stage.stderr = Some(StderrTarget::File(path, false)),
+                        Token::StderrAppend => stage.stderr = Some(StderrTarget::File(path, true)),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+        }
+        if stage.args.is_empty() {
+            return Err(parse_error(cmd, "missing command"));
+        }
+        pipeline.stages.push(stage);
+
+        Ok(pipeline)
+    }
+
+    /// Appends a command to the pipeline.
+    ///
+    /// The command must be a simple command with some optional arguments.
+    pub fn pipe(mut self, cmd: &str) -> Pipeline {
+        match shlex::split(cmd) {
+            Some(args) if !args.is_empty() => self.stages.push(Stage {
+                args,
+                ..Default::default()
+            }),
+            _ => self.set_error(format!("cannot parse command {:?}", cmd)),
+        }
+        self
+    }
+
+    /// The last command reads its stdin from the given file (`< path`).
+    pub fn stdin_file<P: AsRef<Path>>(mut self, path: P) -> Pipeline {
+        if let Some(stage) = self.last_stage() {
+            stage.stdin = Some(path.as_ref().to_path_buf());
+        }
+        self
+    }
+
+    /// The stdout of the last command is written to the given file (`> path`).
+    pub fn stdout_file<P: AsRef<Path>>(mut self, path: P) -> Pipeline {
+        if let Some(stage) = self.last_stage() {
+            stage.stdout = Some((path.as_ref().to_path_buf(), false));
+        }
+        self
+    }
+
+    /// The stdout of the last command is appended to the given file (`>> path`).
+    pub fn stdout_append<P: AsRef<Path>>(mut self, path: P) -> Pipeline {
+        if let Some(stage) = self.last_stage() {
+            stage.stdout = Some((path.as_ref().to_path_buf(), true));
+        }
+        self
+    }
+
+    /// The stderr of the last command is written to the given file (`2> path`).
+    pub fn stderr_file<P: AsRef<Path>>(mut self, path: P) -> Pipeline {
+        if let Some(stage) = self.last_stage() {
+            stage.stderr = Some(StderrTarget::File(path.as_ref().to_path_buf(), false));
+        }
+        self
+    }
+
+    /// The stderr of the last command is appended to the given file (`2>> path`).
+    pub fn stderr_append<P: AsRef<Path>>(mut self, path: P) -> Pipeline {
+        if let Some(stage) = self.last_stage() {
+            stage.stderr = Some(StderrTarget::File(path.as_ref().to_path_buf(), true));
+        }
+        self
+    }
+
+    /// The stderr of the last command goes where its stdout goes (`2>&1`).
+    pub fn stderr_to_stdout(mut self) -> Pipeline {
+        if let Some(stage) = self.last_stage() {
+            stage.stderr = Some(StderrTarget::Stdout);
+        }
+        self
+    }
+
+    /// Runs the pipeline and waits for all of its commands to complete.
+    pub fn run(&self) -> Result<PipeStat, Error> {
+        if let Some(msg) = &self.error {
+            return Err(Error::Parse(msg.clone()));
+        }
+        if self.stages.is_empty() {
+            return Err(Error::Parse("empty pipeline".to_string()));
+        }
+
+        let mut running = vec![];
+        let result = self.spawn_stages(&mut running);
+        if let Err(e) = result {
+            for (mut child, _, _) in running {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            return Err(e);
+        }
+
+        // wait for every command (and join the capture threads) even if one of the waits fails
+        let mut stages = vec![];
+        let mut first_error = None;
+        for (mut child, stdout, stderr) in running {
+            let status = child.wait();
+            let stdout = finish_capture(stdout);
+            let stderr = finish_capture(stderr);
+            match status {
+                Ok(status) => stages.push(ProcStat {
+                    exit_code: exit_code_of(status),
+                    stdout,
+                    stderr,
+                    timed_out: false,
+                }),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = first_error {
+            return Err(e.into());
+        }
+        let exit_code = stages
+            .iter()
+            .rev()
+            .map(|stage| stage.exit_code)
+            .find(|&code| code != 0)
+            .unwrap_or(0);
+
+        Ok(PipeStat { exit_code, stages })
+    }
+
+    fn spawn_stages(&self, running: &mut Vec<RunningStage>) -> Result<(), Error> {
+        let last = self.stages.len() - 1;
+        let mut next_stdin: Option<io::PipeReader> = None;
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            let piped = next_stdin.take();
+            let stdin = match &stage.stdin {
+                Some(path) => Stdio::from(File::open(path)?),
+                None => piped.map_or_else(Stdio::null, Stdio::from),
+            };
+
+            // for 2>&1, stderr gets a duplicate of the stdout handle
+            let dup_stdout = matches!(stage.stderr, Some(StderrTarget::Stdout));
+            let mut stdout_reader = None;
+            let (stdout, stdout_dup): (Stdio, Option<Stdio>) = match &stage.stdout {
+                Some((path, append)) => {
+                    let file = open_for_output(path, *append)?;
+                    let dup = if dup_stdout {
+                        Some(file.try_clone()?.into())
+                    } else {
+                        None
+                    };
+                    (file.into(), dup)
+                }
+                None => {
+                    let (reader, writer) = io::pipe()?;
+                    if i == last {
+                        stdout_reader = Some(reader);
+                    } else {
+                        next_stdin = Some(reader);
+                    }
+                    let dup = if dup_stdout {
+                        Some(writer.try_clone()?.into())
+                    } else {
+                        None
+                    };
+                    (writer.into(), dup)
+                }
+            };
+
+            let mut stderr_reader = None;
+            let stderr = match &stage.stderr {
+                Some(StderrTarget::File(path, append)) => open_for_output(path, *append)?.into(),
+                Some(StderrTarget::Stdout) => stdout_dup.unwrap_or_else(Stdio::null),
+                None => {
+                    let (reader, writer) = io::pipe()?;
+                    stderr_reader = Some(reader);
+                    writer.into()
+                }
+            };
+
+            // the command is dropped at the end of the iteration, which closes
+            // our copies of the pipe ends (otherwise the readers would never get EOF)
+            let mut p = process::Command::new(&stage.args[0]);
+            p.args(&stage.args[1..])
+                .stdin(stdin)
+                .stdout(stdout)
+                .stderr(stderr);
+            let child = p.spawn().map_err(|e| spawn_error(&stage.args[0], e))?;
+
+            running.push((
+                child,
+                stdout_reader.map(Capture::start),
+                stderr_reader.map(Capture::start),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn last_stage(&mut self) -> Option<&mut Stage> {
+        if self.stages.is_empty() {
+            self.set_error("redirection before any command".to_string());
+        }
+        self.stages.last_mut()
+    }
+
+    fn set_error(&mut self, msg: String) {
+        // keep the first error
+        self.error.get_or_insert(msg);
+    }
+}
+
+/// Parses and runs a pipeline of external commands, and waits for it to complete.
+///
+/// It's like [`get_exitcode_stdout_stderr`], but the command can contain pipes
+/// and redirections. Supported operators: `|`, `<`, `>`, `>>`, `2>`, `2>>` and `2>&1`.
+///
+/// The result contains a `ProcStat` per stage and a `pipefail`-style exit code
+/// (see [`PipeStat`]).
+///
+/// Similar to `subprocess.run("...", shell=True)` in Python, but no shell is involved.
+///
+/// # Examples
+///
+/// ```
+/// let stat = jabba_lib::jprocess::run_pipeline("echo hello world | tr a-z A-Z").unwrap();
+///
+/// assert_eq!(stat.exit_code, 0);
+/// assert_eq!(stat.stages.len(), 2);
+/// assert_eq!(stat.trimmed_output(), "HELLO WORLD");
+/// ```
+pub fn run_pipeline(cmd: &str) -> Result<PipeStat, Error> {
+    Pipeline::parse(cmd)?.run()
+}
+
+type RunningStage = (process::Child, Option<Capture>, Option<Capture>);
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Pipe,           // |
+    StdinFrom,      // <
+    StdoutTo,       // >
+    StdoutAppend,   // >>
+    StderrTo,       // 2>
+    StderrAppend,   // 2>>
+    StderrToStdout, // 2>&1
+}
+
+/// Splits a command line into words and operators.
+///
+/// Only unquoted operators are recognized. The text between two operators
+/// is split into words with shlex.
+fn tokenize(cmd: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chunk = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = cmd.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            chunk.push(c);
+            if c == q {
+                quote = None;
+            } else if c == '\\' && q == '"' {
+                if let Some(next) = chars.next() {
+                    chunk.push(next);
+                }
+            }
+            continue;
+        }
+        // else, if not inside quotes
+        match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                chunk.push(c);
+            }
+            '\\' => {
+                chunk.push(c);
+                if let Some(next) = chars.next() {
+                    chunk.push(next);
+                }
+            }
+            '|' => {
+                split_chunk(cmd, &mut chunk, &mut tokens)?;
+                tokens.push(Token::Pipe);
+            }
+            '<' => {
+                split_chunk(cmd, &mut chunk, &mut tokens)?;
+                tokens.push(Token::StdinFrom);
+            }
+            '>' => {
+                let is_stderr = ends_with_stderr_fd(&chunk);
+                if is_stderr {
+                    chunk.pop();
+                }
+                split_chunk(cmd, &mut chunk, &mut tokens)?;
+                let append = chars.next_if_eq(&'>').is_some();
+                let token = if chars.next_if_eq(&'&').is_some() {
+                    if !is_stderr || append || chars.next_if_eq(&'1').is_none() {
+                        return Err(parse_error(cmd, "only the '2>&1' duplication is supported"));
+                    }
+                    Token::StderrToStdout
+                } else {
+                    match (is_stderr, append) {
+                        (false, false) => Token::StdoutTo,
+                        (false, true) => Token::StdoutAppend,
+                        (true, false) => Token::StderrTo,
+                        (true, true) => Token::StderrAppend,
+                    }
+                };
+                tokens.push(token);
+            }
+            _ => chunk.push(c),
+        }
+    }
+    split_chunk(cmd, &mut chunk, &mut tokens)?;
+
+    Ok(tokens)
+}
+
+fn split_chunk(cmd: &str, chunk: &mut String, tokens: &mut Vec<Token>) -> Result<(), Error> {
+    let words = shlex::split(chunk).ok_or_else(|| parse_error(cmd, "invalid quoting"))?;
+    tokens.extend(words.into_iter().map(Token::Word));
+    chunk.clear();
+    Ok(())
+}
+
+/// `true` if the chunk ends with a standalone "2", i.e. the next '>' redirects stderr.
+fn ends_with_stderr_fd(chunk: &str) -> bool {
+    match chunk.strip_suffix('2') {
+        Some(rest) => rest.is_empty() || rest.ends_with(char::is_whitespace),
+        None => false,
+    }
+}
+
+fn parse_error(cmd: &str, msg: &str) -> Error {
+    Error::Parse(format!("cannot parse command {:?}: {}", cmd, msg))
+}
+
+/// Converts the error of a failed spawn.
+fn spawn_error(program: &str, e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::NotFound => Error::NotFound(program.to_string()),
+        io::ErrorKind::PermissionDenied => Error::PermissionDenied(program.to_string()),
+        _ => Error::Io(e),
+    }
+}
+
+fn open_for_output(path: &Path, append: bool) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+}
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// how long we wait for the output pipes to be closed after a command was killed
+const GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// Collects the data read from a pipe in a background thread,
+/// thus the pipe can't fill up and block the child process.
+#[derive(Debug)]
+struct Capture {
+    buf: Arc<Mutex<Vec<u8>>>,
+    handle: JoinHandle<()>,
+}
+
+impl Capture {
+    fn start<R: Read + Send + 'static>(mut reader: R) -> Capture {
+        let buf = Arc::new(Mutex::new(vec![]));
+        let shared = Arc::clone(&buf);
+        let handle = thread::spawn(move || {
+            let mut chunk = [0; 8192];
+            loop {
+                match reader.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => shared.lock().unwrap().extend_from_slice(&chunk[..n]),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                }
+            }
+        });
+        Capture { buf, handle }
+    }
+
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buf.lock().unwrap()).to_string()
+    }
+}
+
+/// Waits until the pipe is closed and returns everything that was read from it.
+fn finish_capture(capture: Option<Capture>) -> String {
+    match capture {
+        Some(Capture { buf, handle }) => {
+            let _ = handle.join();
+            let buf = buf.lock().unwrap();
+            String::from_utf8_lossy(&buf).to_string()
+        }
+        None => String::new(),
+    }
+}
+
+/// Like `finish_capture()`, but it waits at most `grace` for the pipe to be closed.
+fn finish_capture_within(capture: Option<Capture>, grace: Duration) -> String {
+    match capture {
+        Some(capture) => {
+            let deadline = Instant::now() + grace;
+            while !capture.handle.is_finished() && Instant::now() < deadline {
+                thread::sleep(POLL_INTERVAL);
+            }
+            capture.contents()
+        }
+        None => String::new(),
+    }
+}
+
+/// Waits for the child to exit. If it doesn't exit within the timeout, it's killed.
+///
+/// The returned flag is `true` if the child was killed because of the timeout.
+fn wait_with_timeout(
+    child: &mut process::Child,
+    timeout: Option<Duration>,
+) -> io::Result<(ExitStatus, bool)> {
+    let deadline = match timeout {
+        Some(timeout) => Instant::now() + timeout,
+        None => return Ok((child.wait()?, false)),
+    };
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, false));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // kill() fails if the child has exited in the meantime; wait() is fine then
+            let _ = child.kill();
+            return Ok((child.wait()?, true));
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Returns the exit code of a process.
+///
+/// If the process was terminated by a signal, it returns 128 + the signal number
+/// (like a shell does).
+fn exit_code_of(status: ExitStatus) -> i32 {
+    match status.code() {
+        Some(code) => code,
+        None => signal_of(status).map_or(-1, |signal| 128 + signal),
+    }
+}
+
+/// Returns the signal that terminated the process (if any).
+fn signal_of(status: ExitStatus) -> Option<i32> {
+    cfg_if::cfg_if! {
+        if #[cfg(unix)] {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal()
+        } else {
+            let _ = status;
+            None
+        }
+    }
+}
+
+// ==========================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_exitcode_stdout_stderr_exit_code_test() {
+        use which::which;
+
+        let cmd = "rustc";
+        let result = which(cmd);
+        if result.is_err() {
+            return;
+        }
+        // else, if "rustc" is available (should be...)
+        let cmd = "rustc --version";
+        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
+        assert_eq!(stat.exit_code, 0);
+    }
+
+    #[test]
+    fn get_exitcode_stdout_stderr_stdout_test() {
+        use which::which;
+
+        let cmd = "rustc";
+        let result = which(cmd);
+        if result.is_err() {
+            return;
+        }
+        // else, if "rustc" is available (should be...)
+        let cmd = "rustc --version";
+        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
+        assert!(stat.stdout.starts_with("rustc"));
+    }
+
+    #[test]
+    fn get_exitcode_stdout_stderr_stderr_test() {
+        use which::which;
+
+        let cmd = "rustc";
+        let result = which(cmd);
+        if result.is_err() {
+            return;
+        }
+        // else, if "rustc" is available (should be...)
+        let cmd = "rustc --nothing20220731"; // this option doesn't exist
+        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
+        assert!(stat.exit_code != 0);
+        assert!(!stat.stderr.is_empty());
+    }
+
+    #[test]
+    fn trimmed_output_test() {
+        use which::which;
+
+        let cmd = "rustc";
+        let result = which(cmd);
+        if result.is_err() {
+            return;
+        }
+        // else, if "rustc" is available (should be...)
+        let cmd = "rustc --version";
+        let stat = get_exitcode_stdout_stderr(cmd).unwrap();
+        assert!(stat.output() == stat.stdout.clone());
+        assert!(stat.trimmed_output().len() < stat.output().len());
+    }
+
+    #[test]
+    fn exec_cmd_test() {
+        let cmd = "rustc --version";
+        exec_cmd(cmd);
+    }
+
+    #[test]
+    fn exec_cmd_in_bg_test() {
+        let cmd = "rustc --version";
+        exec_cmd_in_bg(cmd);
+        //
+        let job = exec_cmd_in_bg(cmd);
+        assert!(job.pid() > 0);
+        assert_eq!(job.wait().unwrap().exit_code, 0);
+    }
+
+    #[test]
+    fn pipeline_parse_test() {
+        let pipeline = Pipeline::parse("ls -al | grep 'a|b' > out.txt").unwrap();
+        assert_eq!(pipeline.stages.len(), 2);
+        assert_eq!(pipeline.stages[0].args, ["ls", "-al"]);
+        assert_eq!(pipeline.stages[1].args, ["grep", "a|b"]);
+        assert_eq!(
+            pipeline.stages[1].stdout,
+            Some((PathBuf::from("out.txt"), false))
+        );
+        //
+        let pipeline = Pipeline::parse("sort<in.txt>>out.txt 2>&1").unwrap();
+        assert_eq!(pipeline.stages.len(), 1);
+        let stage = &pipeline.stages[0];
+        assert_eq!(stage.args, ["sort"]);
+        assert_eq!(stage.stdin, Some(PathBuf::from("in.txt")));
+        assert_eq!(stage.stdout, Some((PathBuf::from("out.txt"), true)));
+        assert!(matches!(stage.stderr, Some(StderrTarget::Stdout)));
+        //
+        let pipeline = Pipeline::parse("make 2> err.txt > out.txt extra").unwrap();
+        let stage = &pipeline.stages[0];
+        assert_eq!(stage.args, ["make", "extra"]);
+        assert!(matches!(stage.stderr, Some(StderrTarget::File(_, false))));
+        //
+        let pipeline = Pipeline::parse(r#"echo "2>" x2> 'y'"#).unwrap();
+        let stage = &pipeline.stages[0];
+        assert_eq!(stage.args, ["echo", "2>", "x2"]);
+        assert_eq!(stage.stdout, Some((PathBuf::from("y"), false)));
+    }
+
+    #[test]
+    fn pipeline_parse_error_test() {
+        for cmd in [
+            "",
+            "ls |",
+            "| ls",
+            "ls > ",
+            "ls | > out.txt",
+            "ls 'abc",
+            "ls >&2",
+        ] {
+            assert!(Pipeline::parse(cmd).is_err(), "{:?}", cmd);
+        }
+        assert!(Pipeline::new().stdout_file("out.txt").run().is_err());
+        assert!(Pipeline::new().pipe("ls 'abc").run().is_err());
+        assert!(Pipeline::new().run().is_err());
+    }
+
+    #[test]
+    fn run_pipeline_test() {
+        use which::which;
+
+        if which("echo").is_err() || which("tr").is_err() || which("wc").is_err() {
+            return;
+        }
+        let stat = run_pipeline("echo hello world | tr a-z A-Z").unwrap();
+        assert_eq!(stat.exit_code, 0);
+        assert_eq!(stat.stages.len(), 2);
+        assert_eq!(stat.stages[0].stdout, "");
+        assert_eq!(stat.output(), "HELLO WORLD\n");
+        assert_eq!(stat.trimmed_output(), "HELLO WORLD");
+        //
+        let stat = run_pipeline("echo 'a | b' | tr -d ' ' | wc -c").unwrap();
+        assert_eq!(stat.stages.len(), 3);
+        assert_eq!(stat.trimmed_output(), "4");
+    }
+
+    #[test]
+    fn run_pipeline_pipefail_test() {
+        use which::which;
+
+        if which("false").is_err() || which("true").is_err() {
+            return;
+        }
+        let stat = run_pipeline("false | true").unwrap();
+        assert_eq!(stat.stages[0].exit_code, 1);
+        assert_eq!(stat.stages[1].exit_code, 0);
+        assert_eq!(stat.exit_code, 1);
+        //
+        let stat = run_pipeline("true | true").unwrap();
+        assert_eq!(stat.exit_code, 0);
+    }
+
+    #[test]
+    fn run_pipeline_redirection_test() {
+        use which::which;
+
+        if which("echo").is_err() || which("cat").is_err() {
+            return;
+        }
+        let fname = "out.20261018a.txt";
+        let stat = run_pipeline(&format!("echo hello > {}", fname)).unwrap();
+        assert_eq!(stat.output(), "");
+        run_pipeline(&format!("echo world >> {}", fname)).unwrap();
+        let stat = run_pipeline(&format!("cat < {}", fname)).unwrap();
+        assert_eq!(stat.output(), "hello\nworld\n");
+        //
+        let stat = Pipeline::new().pipe("cat").stdin_file(fname).run().unwrap();
+        assert_eq!(stat.output(), "hello\nworld\n");
+        std::fs::remove_file(fname).unwrap();
+    }
+
+    #[test]
+    fn run_pipeline_stderr_test() {
+        use which::which;
+
+        if which("ls").is_err() || which("cat").is_err() {
+            return;
+        }
+        let missing = "/nonexistent20261018";
+        let stat = run_pipeline(&format!("ls {} | cat", missing)).unwrap();
+        assert!(stat.stages[0].stderr.contains(missing));
+        assert_eq!(stat.output(), "");
+        assert!(stat.exit_code != 0);
+        //
+        let stat = run_pipeline(&format!("ls {} 2>&1 | cat", missing)).unwrap();
+        assert_eq!(stat.stages[0].stderr, "");
+        assert!(stat.output().contains(missing));
+    }
+
+    #[test]
+    fn run_pipeline_missing_command_test() {
+        let result = run_pipeline("nothing20261018 | cat");
+        assert!(matches!(result, Err(Error::NotFound(program)) if program == "nothing20261018"));
+    }
+
+    #[test]
+    fn cmd_output_test() {
+        use which::which;
+
+        if which("tr").is_err() {
+            return;
+        }
+        let stat = Cmd::new("tr")
+            .args(["a-z", "A-Z"])
+            .stdin_str("hello")
+            .output()
+            .unwrap();
+        assert_eq!(stat.exit_code, 0);
+        assert_eq!(stat.stdout, "HELLO");
+        assert_eq!(stat.stderr, "");
+        assert_eq!(stat.timed_out, false);
+        //
+        assert!(Cmd::new("nothing20261018").output().is_err());
+    }
+
+    #[test]
+    fn cmd_large_stdin_test() {
+        use which::which;
+
+        if which("cat").is_err() {
+            return;
+        }
+        // much more than a pipe's buffer in both directions
+        let text = "jabba\n".repeat(200_000);
+        let stat = Cmd::new("cat").stdin_str(&text).output().unwrap();
+        assert_eq!(stat.stdout.len(), text.len());
+        assert!(stat.stdout == text);
+    }
+
+    #[test]
+    fn cmd_cwd_and_env_test() {
+        use which::which;
+
+        if which("ls").is_err() || which("printenv").is_err() {
+            return;
+        }
+        let stat = Cmd::new("ls").cwd("src").output().unwrap();
+        assert!(stat.stdout.lines().any(|line| line == "lib.rs"));
+        //
+        let stat = Cmd::new("printenv")
+            .arg("JABBA_20261018")
+            .env("JABBA_20261018", "42")
+            .output()
+            .unwrap();
+        assert_eq!(stat.trimmed_output(), "42");
+        //
+        let stat = Cmd::new("printenv")
+            .arg("JABBA_20261018")
+            .env("JABBA_20261018", "42")
+            .env_remove("JABBA_20261018")
+            .output()
+            .unwrap();
+        assert!(stat.exit_code != 0);
+        assert_eq!(stat.stdout, "");
+    }
+
+    #[test]
+    fn cmd_timeout_test() {
+        use which::which;
+
+        if which("sleep").is_err() || which("true").is_err() {
+            return;
+        }
+        let start = Instant::now();
+        let stat = Cmd::new("sleep").arg("10").timeout(0.2).output().unwrap();
+        assert!(stat.timed_out);
+        assert!(stat.exit_code != 0);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        //
+        let stat = Cmd::new("true").timeout(10.0).output().unwrap();
+        assert_eq!(stat.timed_out, false);
+        assert_eq!(stat.exit_code, 0);
+    }
+
+    #[test]
+    fn try_get_exitcode_stdout_stderr_error_test() {
+        let result = try_get_exitcode_stdout_stderr("");
+        assert!(matches!(result, Err(Error::Parse(_))));
+        let result = try_get_exitcode_stdout_stderr("echo 'abc");
+        assert!(matches!(result, Err(Error::Parse(_))));
+        let result = try_get_exitcode_stdout_stderr("nothing20261018 --version");
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        // exists, but it's not executable
+        let result = try_get_exitcode_stdout_stderr("./Cargo.toml");
+        assert!(matches!(result, Err(Error::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn try_get_exitcode_stdout_stderr_signal_test() {
+        use which::which;
+
+        if !cfg!(unix) || which("sh").is_err() {
+            return;
+        }
+        let result = try_get_exitcode_stdout_stderr("sh -c 'kill -9 $$'");
+        assert!(matches!(result, Err(Error::Signal(9))));
+        assert!(get_exitcode_stdout_stderr("sh -c 'kill -9 $$'").is_none());
+        let result = try_exec_cmd("sh -c 'kill -15 $$'");
+        assert!(matches!(result, Err(Error::Signal(15))));
+        // in a pipeline, it's an exit code like in a shell
+        let stat = run_pipeline("sh -c 'kill -9 $$'").unwrap();
+        assert_eq!(stat.exit_code, 128 + 9);
+    }
+
+    #[test]
+    fn try_exec_cmd_test() {
+        use which::which;
+
+        if which("false").is_err() {
+            return;
+        }
+        assert_eq!(try_exec_cmd("false").unwrap(), 1);
+        assert!(matches!(
+            try_exec_cmd("nothing20261018"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            try_exec_cmd_in_bg("nothing20261018"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(try_exec_cmd_in_bg(""), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn error_display_test() {
+        let e = Error::NotFound("abc".to_string());
+        assert_eq!(e.to_string(), r#"command "abc" was not found"#);
+        assert_eq!(
+            Error::Signal(9).to_string(),
+            "process terminated by signal 9"
+        );
+        assert_eq!(Error::Timeout.to_string(), "process timed out");
+    }
+
+    #[test]
+    fn cmd_lines_test() {
+        use which::which;
+
+        if which("sh").is_err() {
+            return;
+        }
+        let mut lines = Cmd::new("sh")
+            .args(["-c", "echo out1; echo err1 >&2; printf 'out2\\r\\nout3'"])
+            .lines()
+            .unwrap();
+        let items: Vec<(Stream, String)> = lines.by_ref().collect();
+        let stdout: Vec<&str> = items
+            .iter()
+            .filter(|(stream, _)| *stream == Stream::Stdout)
+            .map(|(_, line)| line.as_str())
+            .collect();
+        assert_eq!(stdout, ["out1", "out2", "out3"]);
+        assert!(items.contains(&(Stream::Stderr, "err1".to_string())));
+        //
+        let stat = lines.finish().unwrap();
+        assert_eq!(stat.exit_code, 0);
+        assert_eq!(stat.stdout, "out1\nout2\r\nout3");
+        assert_eq!(stat.stderr, "err1\n");
+    }
+
+    #[test]
+    fn cmd_lines_live_test() {
+        use which::which;
+
+        if which("sh").is_err() {
+            return;
+        }
+        let start = Instant::now();
+        let mut lines = Cmd::new("sh")
+            .args(["-c", "echo first; sleep 10; echo second"])
+            .lines()
+            .unwrap();
+        assert_eq!(lines.next(), Some((Stream::Stdout, "first".to_string())));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(lines); // kills the command
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn cmd_stream_large_output_test() {
+        use which::which;
+
+        if which("sh").is_err() || which("seq").is_err() {
+            return;
+        }
+        // fills up both pipes
+        let script = "seq 1 100000; seq 1 100000 >&2; seq 1 100000";
+        let mut counts = (0, 0);
+        let stat = Cmd::new("sh")
+            .args(["-c", script])
+            .stream(|stream, _| match stream {
+                Stream::Stdout => counts.0 += 1,
+                Stream::Stderr => counts.1 += 1,
+            })
+            .unwrap();
+        assert_eq!(counts, (200_000, 100_000));
+        assert_eq!(stat.stdout.lines().count(), 200_000);
+        assert_eq!(stat.stderr.lines().count(), 100_000);
+    }
+
+    #[test]
+    fn cmd_stream_timeout_test() {
+        use which::which;
+
+        if which("sh").is_err() {
+            return;
+        }
+        let start = Instant::now();
+        let mut seen = vec![];
+        let stat = Cmd::new("sh")
+            .args(["-c", "echo a; sleep 10; echo b"])
+            .timeout(0.3)
+            .stream(|_, line| seen.push(line.to_string()))
+            .unwrap();
+        assert!(stat.timed_out);
+        assert_eq!(seen, ["a"]);
+        assert_eq!(stat.stdout, "a\n");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn stream_cmd_test() {
+        let result = stream_cmd("nothing20261018", |_, _| ());
+        assert!(matches!(result, Err(Error::NotFound(_))));
+        //
+        let mut lines = vec![];
+        let stat = stream_cmd("rustc --version", |_, line| lines.push(line.to_string())).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(stat.trimmed_output(), lines[0]);
+    }
+
+    #[test]
+    fn job_wait_test() {
+        use which::which;
+
+        if which("sh").is_err() {
+            return;
+        }
+        let job = Cmd::new("sh")
+            .args(["-c", "echo hi; echo err >&2; exit 3"])
+            .capture()
+            .spawn()
+            .unwrap();
+        let stat = job.wait().unwrap();
+        assert_eq!(stat.exit_code, 3);
+        assert_eq!(stat.stdout, "hi\n");
+        assert_eq!(stat.stderr, "err\n");
+        //
+        let result = Cmd::new("nothing20261018").spawn();
+        assert!(matches!(result, Err(Error::NotFound(_))));
+    }
+
+    #[test]
+    fn job_try_wait_and_kill_test() {
+        use which::which;
+
+        if !cfg!(unix) || which("sleep").is_err() {
+            return;
+        }
+        let mut job = Cmd::new("sleep").arg("10").spawn().unwrap();
+        assert!(job.is_running());
+        assert_eq!(job.try_wait().unwrap(), None);
+        job.kill().unwrap();
+        assert!(matches!(job.wait(), Err(Error::Signal(9))));
+        //
+        let mut job = Cmd::new("sleep").arg("0").spawn().unwrap();
+        while job.is_running() {
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert_eq!(job.try_wait().unwrap(), Some(0));
+        job.kill().unwrap(); // already reaped, nothing to do
+        assert_eq!(job.wait().unwrap().exit_code, 0);
+    }
+
+    #[test]
+    fn job_timeout_test() {
+        use which::which;
+
+        if which("sleep").is_err() {
+            return;
+        }
+        let start = Instant::now();
+        let job = Cmd::new("sleep").arg("10").timeout(0.2).spawn().unwrap();
+        assert!(matches!(job.wait(), Err(Error::Timeout)));
+        //
+        let mut job = Cmd::new("sleep").arg("10").timeout(0.1).spawn().unwrap();
+        thread::sleep(Duration::from_millis(200));
+        assert!(matches!(job.try_wait(), Err(Error::Timeout)));
+        assert!(matches!(job.try_wait(), Err(Error::Timeout)));
+        assert!(!job.is_running());
+        assert!(matches!(job.wait(), Err(Error::Timeout)));
+        //
+        let mut table = JobTable::new();
+        table.add(Cmd::new("sleep").arg("10").timeout(0.1).spawn().unwrap());
+        thread::sleep(Duration::from_millis(200));
+        let finished = table.reap();
+        assert!(matches!(finished[0].1, Err(Error::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn job_drop_test() {
+        use which::which;
+
+        if which("sleep").is_err() {
+            return;
+        }
+        let job = Cmd::new("sleep").arg("0.1").spawn().unwrap();
+        let pid = job.pid();
+        drop(job);
+        let is_orphan = |pid| ORPHANS.lock().unwrap().iter().any(|c| c.id() == pid);
+        assert!(is_orphan(pid));
+        thread::sleep(Duration::from_millis(300));
+        // starting a new job reaps the finished orphans
+        Cmd::new("sleep").arg("0").spawn().unwrap().wait().unwrap();
+        assert!(!is_orphan(pid));
+    }
+
+    #[test]
+    fn job_table_test() {
+        use which::which;
+
+        if which("sleep").is_err() || which("true").is_err() || which("false").is_err() {
+            return;
+        }
+        let mut table = JobTable::new();
+        let id1 = table.add(Cmd::new("true").spawn().unwrap());
+        let id2 = table.add(Cmd::new("false").spawn().unwrap());
+        let id3 = table.add(Cmd::new("sleep").arg("10").spawn().unwrap());
+        assert_eq!((id1, id2, id3), (1, 2, 3));
+        assert_eq!(table.len(), 3);
+        //
+        let mut finished = vec![];
+        while finished.len() < 2 {
+            finished.extend(table.reap());
+            thread::sleep(POLL_INTERVAL);
+        }
+        finished.sort_by_key(|(id, _)| *id);
+        assert_eq!(finished[0].1.as_ref().unwrap().exit_code, 0);
+        assert_eq!(finished[1].1.as_ref().unwrap().exit_code, 1);
+        assert_eq!(table.ids(), [id3]);
+        assert!(table.get(id3).unwrap().is_running());
+        assert!(table.get(id1).is_none());
+        //
+        table.kill_all();
+        let finished = table.wait_all();
+        assert_eq!(finished.len(), 1);
+        assert!(finished[0].1.is_err());
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn run_parallel_test() {
+        use which::which;
+
+        if which("sh").is_err() {
+            return;
+        }
+        let commands: Vec<String> = (0..20)
+            .map(|i| format!("sh -c 'sleep 0.0{}; echo {}'", i % 3, i))
+            .collect();
+        let results = run_parallel(&commands);
+        assert_eq!(results.len(), 20);
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result.unwrap().trimmed_output(), i.to_string());
+        }
+        //
+        let results = run_parallel(&["rustc --version", "echo 'abc", "nothing20261018"]);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::Parse(_))));
+        assert!(matches!(results[2], Err(Error::NotFound(_))));
+        //
+        assert!(run_parallel::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn parallel_max_jobs_test() {
+        use which::which;
+
+        if which("sleep").is_err() {
+            return;
+        }
+        let commands = ["sleep 0.2"; 4];
+        let start = Instant::now();
+        let results = Parallel::new(&commands).max_jobs(1).run();
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert!(start.elapsed() >= Duration::from_millis(800));
+        //
+        let start = Instant::now();
+        Parallel::new(&commands).max_jobs(4).run();
+        assert!(start.elapsed() < Duration::from_millis(800));
+    }
+
+    #[test]
+    fn parallel_fail_fast_test() {
+        use which::which;
+
+        if which("true").is_err() || which("false").is_err() {
+            return;
+        }
+        let commands = ["true", "false", "true", "true"];
+        let results = Parallel::new(&commands).max_jobs(1).fail_fast(true).run();
+        assert_eq!(results[0].as_ref().unwrap().exit_code, 0);
+        assert_eq!(results[1].as_ref().unwrap().exit_code, 1);
+        assert!(matches!(results[2], Err(Error::Cancelled)));
+        assert!(matches!(results[3], Err(Error::Cancelled)));
+        //
+        let results = Parallel::new(&commands).max_jobs(1).run();
+        assert!(results.iter().all(|result| result.is_ok()));
+        //
+        let results = Parallel::new(&["true", "echo 'abc"]).fail_fast(true).run();
+        assert!(matches!(results[0], Err(Error::Cancelled)));
+        assert!(matches!(results[1], Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn parallel_progress_test() {
+        let commands = ["rustc --version"; 5];
+        let mut calls = vec![];
+        let results = Parallel::new(&commands)
+            .max_jobs(2)
+            .run_with_progress(|done, total| calls.push((done, total)));
+        assert_eq!(results.len(), 5);
+        assert_eq!(calls, [(1, 5), (2, 5), (3, 5), (4, 5), (5, 5)]);
     }
 }
@@ -108,7 +108,7 @@ mod tests {
         //
         for _ in 0..1000 {
             let value = randrange(1, 10);
-            assert!(value >= 1 && value < 10);
+            assert!((1..10).contains(&value));
         }
     }
 
@@ -121,12 +121,12 @@ mod tests {
         //
         for _ in 0..10 {
             let value = randint(1, 2);
-            assert!(value >= 1 && value <= 2);
+            assert!((1..=2).contains(&value));
         }
         //
         for _ in 0..1000 {
             let value = randint(1, 10);
-            assert!(value >= 1 && value <= 10);
+            assert!((1..=10).contains(&value));
         }
     }
 
@@ -188,7 +188,7 @@ mod tests {
     fn random_test1() {
         for _ in 0..100 {
             let number = random();
-            assert!(number >= 0.0 && number < 1.0)
+            assert!((0.0..1.0).contains(&number))
         }
     }
 
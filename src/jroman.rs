@@ -0,0 +1,355 @@
+//! roman numerals
+
+use std::fmt;
+
+/// The error returned by the functions of this module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The number cannot be written with the standard Roman numerals (only 1..=3999 can).
+    OutOfRange(u64),
+    /// The string is empty.
+    Empty,
+    /// A character that is not a Roman numeral.
+    InvalidChar(char),
+    /// The numeral is readable, but it's not in the canonical form.
+    /// It contains the canonical form of the same number (e.g. "IV" for "IIII").
+    NotCanonical(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfRange(n) => write!(f, "{} is out of range (1..=3999)", n),
+            Error::Empty => write!(f, "empty Roman numeral"),
+            Error::InvalidChar(c) => write!(f, "invalid character: {:?}", c),
+            Error::NotCanonical(canonical) => {
+                write!(
+                    f,
+                    "not a canonical Roman numeral (did you mean {}?)",
+                    canonical
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The largest number that can be written with the standard Roman numerals.
+pub const MAX: u32 = 3999;
+
+/// Converts a number to Roman numerals (in the canonical, subtractive form).
+///
+/// The number must be between 1 and 3999 (inclusive).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jroman::{to_roman, Error};
+///
+/// assert_eq!(to_roman(4).unwrap(), "IV");
+/// assert_eq!(to_roman(1984).unwrap(), "MCMLXXXIV");
+/// assert_eq!(to_roman(0), Err(Error::OutOfRange(0)));
+/// ```
+pub fn to_roman(n: u32) -> Result<String, Error> {
+    if !(1..=MAX).contains(&n) {
+        return Err(Error::OutOfRange(n as u64));
+    }
+    // else
+    let mut result = String::new();
+    let mut rest = n;
+    for &(value, numeral) in NUMERALS.iter() {
+        while rest >= value {
+            result.push_str(numeral);
+            rest -= value;
+        }
+    }
+    Ok(result)
+}
+
+/// Like [`to_roman`], but uses the Unicode Roman numeral characters
+/// (e.g. "Ⅿ" (U+216F) instead of "M").
+///
+/// Every letter is converted one by one, thus 12 is "ⅩⅠⅠ" (and not the single character "Ⅻ").
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jroman::to_roman_unicode;
+///
+/// assert_eq!(to_roman_unicode(2024).unwrap(), "ⅯⅯⅩⅩⅠⅤ");
+/// ```
+pub fn to_roman_unicode(n: u32) -> Result<String, Error> {
+    let ascii = to_roman(n)?;
+    Ok(ascii
+        .chars()
+        .map(|c| UNICODE_LETTERS.iter().find(|(a, _)| *a == c).unwrap().1)
+        .collect())
+}
+
+/// Converts a Roman numeral to a number.
+///
+/// The numeral must be in the canonical form, i.e. exactly what [`to_roman`]
+/// (or [`to_roman_unicode`]) produces: uppercase, subtractive ("IV" and not "IIII"),
+/// between 1 and 3999. See [`from_roman_lenient`] for a forgiving version.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jroman::{from_roman, Error};
+///
+/// assert_eq!(from_roman("MCMLXXXIV"), Ok(1984));
+/// assert_eq!(from_roman("ⅯⅯⅩⅩⅠⅤ"), Ok(2024));
+/// assert_eq!(from_roman("IIII"), Err(Error::NotCanonical("IV".to_string())));
+/// assert_eq!(from_roman("IIX"), Err(Error::NotCanonical("VIII".to_string())));
+/// assert_eq!(from_roman("MXQ"), Err(Error::InvalidChar('Q')));
+/// ```
+pub fn from_roman(s: &str) -> Result<u32, Error> {
+    let mut values = vec![];
+    for c in s.chars() {
+        values.push(letter_value(c).ok_or(Error::InvalidChar(c))?);
+    }
+    let n = evaluate(&values)?;
+    let canonical = to_roman(n)?;
+    let ascii: String = s.chars().map(to_ascii_letter).collect();
+    if ascii != canonical {
+        return Err(Error::NotCanonical(canonical));
+    }
+    Ok(n)
+}
+
+/// Converts a Roman numeral to a number, accepting the non-canonical forms too.
+///
+/// The case doesn't matter, the surrounding whitespace is ignored, the additive forms
+/// ("IIII", "VIIII") and the irregular subtractive forms ("IC", "IIX") are accepted,
+/// and so are the Unicode Roman numerals (including the compound ones, like "Ⅻ").
+/// A run of equal letters before a larger one is subtracted as a whole ("IIX" is 8,
+/// "XXC" is 80), otherwise it's added.
+/// Numbers above 3999 are accepted (e.g. "MMMM" is 4000).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jroman::from_roman_lenient;
+///
+/// assert_eq!(from_roman_lenient("mcmlxxxiv"), Ok(1984));
+/// assert_eq!(from_roman_lenient("IIII"), Ok(4));
+/// assert_eq!(from_roman_lenient("IC"), Ok(99));
+/// assert_eq!(from_roman_lenient("IIX"), Ok(8));
+/// assert_eq!(from_roman_lenient(" Ⅻ "), Ok(12));
+/// assert_eq!(from_roman_lenient("MMMM"), Ok(4000));
+/// ```
+pub fn from_roman_lenient(s: &str) -> Result<u32, Error> {
+    let mut values = vec![];
+    for c in s.trim().chars() {
+        let upper = c.to_uppercase().next().unwrap_or(c); // 'x' -> 'X', 'ⅻ' -> 'Ⅻ'
+        match COMPOUNDS.iter().find(|(compound, _)| *compound == upper) {
+            Some((_, letters)) => {
+                values.extend(letters.chars().map(|c| letter_value(c).unwrap()));
+            }
+            None => values.push(letter_value(upper).ok_or(Error::InvalidChar(c))?),
+        }
+    }
+    evaluate(&values)
+}
+
+/*********
+  private
+**********/
+
+/// The values with their canonical numerals, in decreasing order.
+const NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// The letters with their values.
+const LETTERS: [(char, u32); 7] = [
+    ('I', 1),
+    ('V', 5),
+    ('X', 10),
+    ('L', 50),
+    ('C', 100),
+    ('D', 500),
+    ('M', 1000),
+];
+
+/// The letters with their Unicode counterparts.
+const UNICODE_LETTERS: [(char, char); 7] = [
+    ('I', 'Ⅰ'),
+    ('V', 'Ⅴ'),
+    ('X', 'Ⅹ'),
+    ('L', 'Ⅼ'),
+    ('C', 'Ⅽ'),
+    ('D', 'Ⅾ'),
+    ('M', 'Ⅿ'),
+];
+
+/// The Unicode numerals that stand for several letters.
+const COMPOUNDS: [(char, &str); 9] = [
+    ('Ⅱ', "II"),
+    ('Ⅲ', "III"),
+    ('Ⅳ', "IV"),
+    ('Ⅵ', "VI"),
+    ('Ⅶ', "VII"),
+    ('Ⅷ', "VIII"),
+    ('Ⅸ', "IX"),
+    ('Ⅺ', "XI"),
+    ('Ⅻ', "XII"),
+];
+
+/// The value of an uppercase letter (ASCII or its Unicode counterpart).
+fn letter_value(c: char) -> Option<u32> {
+    let c = to_ascii_letter(c);
+    LETTERS
+        .iter()
+        .find(|(letter, _)| *letter == c)
+        .map(|(_, value)| *value)
+}
+
+/// "Ⅹ" (U+2169) -> 'X'. Any other character is returned as it is.
+fn to_ascii_letter(c: char) -> char {
+    match UNICODE_LETTERS.iter().find(|(_, u)| *u == c) {
+        Some((ascii, _)) => *ascii,
+        None => c,
+    }
+}
+
+/// A run of equal values before a larger one is subtracted (e.g. "IIX" is 8),
+/// otherwise it's added.
+fn evaluate(values: &[u32]) -> Result<u32, Error> {
+    if values.is_empty() {
+        return Err(Error::Empty);
+    }
+    // else
+    let runs: Vec<&[u32]> = values.chunk_by(|a, b| a == b).collect();
+    let mut total: i64 = 0;
+    for (i, run) in runs.iter().enumerate() {
+        let sum = run[0] as i64 * run.len() as i64;
+        match runs.get(i + 1) {
+            Some(next) if run[0] < next[0] => total -= sum,
+            _ => total += sum,
+        }
+    }
+    match u32::try_from(total) {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(Error::OutOfRange(total.max(0) as u64)),
+    }
+}
+
+// ==========================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_roman_test() {
+        assert_eq!(to_roman(1).unwrap(), "I");
+        assert_eq!(to_roman(3).unwrap(), "III");
+        assert_eq!(to_roman(4).unwrap(), "IV");
+        assert_eq!(to_roman(9).unwrap(), "IX");
+        assert_eq!(to_roman(14).unwrap(), "XIV");
+        assert_eq!(to_roman(40).unwrap(), "XL");
+        assert_eq!(to_roman(90).unwrap(), "XC");
+        assert_eq!(to_roman(400).unwrap(), "CD");
+        assert_eq!(to_roman(444).unwrap(), "CDXLIV");
+        assert_eq!(to_roman(900).unwrap(), "CM");
+        assert_eq!(to_roman(1984).unwrap(), "MCMLXXXIV");
+        assert_eq!(to_roman(3888).unwrap(), "MMMDCCCLXXXVIII");
+        assert_eq!(to_roman(3999).unwrap(), "MMMCMXCIX");
+        //
+        assert_eq!(to_roman(0), Err(Error::OutOfRange(0)));
+        assert_eq!(to_roman(4000), Err(Error::OutOfRange(4000)));
+    }
+
+    #[test]
+    fn to_roman_unicode_test() {
+        assert_eq!(to_roman_unicode(12).unwrap(), "ⅩⅠⅠ");
+        assert_eq!(to_roman_unicode(3999).unwrap(), "ⅯⅯⅯⅭⅯⅩⅭⅠⅩ");
+        assert_eq!(to_roman_unicode(0), Err(Error::OutOfRange(0)));
+    }
+
+    #[test]
+    fn from_roman_test() {
+        assert_eq!(from_roman("I"), Ok(1));
+        assert_eq!(from_roman("IV"), Ok(4));
+        assert_eq!(from_roman("XLII"), Ok(42));
+        assert_eq!(from_roman("MMMCMXCIX"), Ok(3999));
+        assert_eq!(from_roman("ⅩⅬⅠⅠ"), Ok(42));
+        //
+        let not_canonical = |s: &str| Err(Error::NotCanonical(s.to_string()));
+        assert_eq!(from_roman(""), Err(Error::Empty));
+        assert_eq!(from_roman("iv"), Err(Error::InvalidChar('i')));
+        assert_eq!(from_roman(" IV"), Err(Error::InvalidChar(' ')));
+        assert_eq!(from_roman("Ⅻ"), Err(Error::InvalidChar('Ⅻ')));
+        assert_eq!(from_roman("IIII"), not_canonical("IV"));
+        assert_eq!(from_roman("VIIII"), not_canonical("IX"));
+        assert_eq!(from_roman("IC"), not_canonical("XCIX"));
+        assert_eq!(from_roman("IIX"), not_canonical("VIII"));
+        assert_eq!(from_roman("VX"), not_canonical("V"));
+        assert_eq!(from_roman("XM"), not_canonical("CMXC"));
+        assert_eq!(from_roman("MDCD"), not_canonical("MCM"));
+        assert_eq!(from_roman("MMMM"), Err(Error::OutOfRange(4000)));
+    }
+
+    #[test]
+    fn roundtrip_test() {
+        let mut total_len = 0;
+        for n in 1..MAX + 1 {
+            let roman = to_roman(n).unwrap();
+            assert_eq!(from_roman(&roman), Ok(n));
+            assert_eq!(from_roman_lenient(&roman.to_lowercase()), Ok(n));
+            assert_eq!(from_roman(&to_roman_unicode(n).unwrap()), Ok(n));
+            total_len += roman.len();
+        }
+        assert_eq!(total_len, 30000);
+    }
+
+    #[test]
+    fn from_roman_lenient_test() {
+        assert_eq!(from_roman_lenient("xlii"), Ok(42));
+        assert_eq!(from_roman_lenient("  XlIi\n"), Ok(42));
+        assert_eq!(from_roman_lenient("IIII"), Ok(4));
+        assert_eq!(from_roman_lenient("XIIII"), Ok(14));
+        assert_eq!(from_roman_lenient("IIX"), Ok(8));
+        assert_eq!(from_roman_lenient("XXC"), Ok(80));
+        assert_eq!(from_roman_lenient("IIXX"), Ok(18));
+        assert_eq!(from_roman_lenient("IC"), Ok(99));
+        assert_eq!(from_roman_lenient("MDCCCCX"), Ok(1910));
+        assert_eq!(from_roman_lenient("MMMMM"), Ok(5000));
+        assert_eq!(from_roman_lenient("Ⅻ"), Ok(12));
+        assert_eq!(from_roman_lenient("ⅻ"), Ok(12));
+        assert_eq!(from_roman_lenient("ⅯⅭⅯⅬⅩⅩⅩⅣ"), Ok(1984));
+        assert_eq!(from_roman_lenient("ⅿⅿⅹⅹⅳ"), Ok(2024));
+        assert_eq!(from_roman_lenient("ⅯⅯⅩⅩⅣ"), Ok(2024));
+        //
+        assert_eq!(from_roman_lenient(""), Err(Error::Empty));
+        assert_eq!(from_roman_lenient("   "), Err(Error::Empty));
+        assert_eq!(from_roman_lenient("X V"), Err(Error::InvalidChar(' ')));
+        assert_eq!(from_roman_lenient("XIIj"), Err(Error::InvalidChar('j')));
+    }
+
+    #[test]
+    fn error_display_test() {
+        assert_eq!(
+            Error::OutOfRange(0).to_string(),
+            "0 is out of range (1..=3999)"
+        );
+        assert_eq!(
+            Error::NotCanonical("IV".to_string()).to_string(),
+            "not a canonical Roman numeral (did you mean IV?)"
+        );
+    }
+}
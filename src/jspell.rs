@@ -1,17 +1,31 @@
 //! spell
 
+use num_bigint::{BigInt, Sign};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
-
-use crate::jmath;
+use std::fmt;
 
 /*********
   public
 **********/
 
+/// The style of spelling.
+///
+/// British style puts "and" after "hundred" and before the last part of the number
+/// (e.g. "one hundred and five", "one thousand and five"); American style doesn't
+/// (e.g. "one hundred five", "one thousand five").
+///
+/// Negative numbers are "minus ..." in British style and "negative ..." in American style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    British,
+    American,
+}
+
 /// Returns the spelled (written out in words) version of the given number.
 ///
-/// Current limitation: the number must be between 0 and 1000 (inclusive).
+/// British style is used (see [`spell_u64`] for the American style).
 ///
 /// # Examples
 ///
@@ -19,25 +33,536 @@ use crate::jmath;
 /// assert_eq!(jabba_lib::jspell::spell_number(0), "zero");
 /// assert_eq!(jabba_lib::jspell::spell_number(115), "one hundred and fifteen");
 /// assert_eq!(jabba_lib::jspell::spell_number(342), "three hundred and forty-two");
+/// assert_eq!(jabba_lib::jspell::spell_number(2022), "two thousand and twenty-two");
 /// ```
 pub fn spell_number(n: u32) -> String {
-    assert!(n <= 1000);
+    spell_u64(n as u64, Style::British)
+}
+
+/// Returns the spelled version of the given number, using short-scale names
+/// (thousand, million, billion, ...).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_u64, Style};
+///
+/// assert_eq!(spell_u64(1_000_042, Style::British), "one million and forty-two");
+/// assert_eq!(spell_u64(1_000_042, Style::American), "one million forty-two");
+/// assert_eq!(
+///     spell_u64(7_250_000_105, Style::American),
+///     "seven billion two hundred fifty million one hundred five"
+/// );
+/// ```
+pub fn spell_u64(n: u64, style: Style) -> String {
+    spell_digits(&n.to_string(), style)
+}
+
+/// Returns the spelled version of the given (possibly negative) number.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_i64, Style};
+///
+/// assert_eq!(spell_i64(-5, Style::British), "minus five");
+/// assert_eq!(spell_i64(-105, Style::American), "negative one hundred five");
+/// assert_eq!(spell_i64(0, Style::American), "zero");
+/// ```
+pub fn spell_i64(n: i64, style: Style) -> String {
+    let spelled = spell_u64(n.unsigned_abs(), style);
+    if n < 0 {
+        with_sign(&spelled, style)
+    } else {
+        spelled
+    }
+}
+
+/// Returns the spelled version of the given big integer.
+///
+/// Above the vigintillion (10^63), the names are combined (e.g. 10^66 is
+/// "one thousand vigintillion").
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_bigint, Style};
+/// use num_bigint::BigInt;
+///
+/// let n: BigInt = "-12000000000000000000000".parse().unwrap();
+/// assert_eq!(spell_bigint(&n, Style::British), "minus twelve sextillion");
+/// ```
+pub fn spell_bigint(n: &BigInt, style: Style) -> String {
+    let spelled = spell_digits(&n.magnitude().to_string(), style);
+    if n.sign() == Sign::Minus {
+        with_sign(&spelled, style)
+    } else {
+        spelled
+    }
+}
+
+/// Returns the spelled ordinal version of the given number.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_ordinal, Style};
+///
+/// assert_eq!(spell_ordinal(1, Style::British), "first");
+/// assert_eq!(spell_ordinal(42, Style::British), "forty-second");
+/// assert_eq!(spell_ordinal(120, Style::American), "one hundred twentieth");
+/// assert_eq!(spell_ordinal(1_000_000, Style::British), "one millionth");
+/// ```
+pub fn spell_ordinal(n: u64, style: Style) -> String {
+    to_ordinal(&spell_u64(n, style))
+}
+
+/// Returns the spelled version of the given decimal number. The digits after the
+/// decimal point are spelled one by one.
+///
+/// The number is written with the shortest representation that reads back
+/// the same float (like `println!("{}", x)` does), thus `0.1` is "zero point one".
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_decimal, Style};
+///
+/// assert_eq!(spell_decimal(3.14, Style::British), "three point one four");
+/// assert_eq!(spell_decimal(-0.05, Style::American), "negative zero point zero five");
+/// assert_eq!(spell_decimal(120.0, Style::British), "one hundred and twenty");
+/// ```
+pub fn spell_decimal(x: f64, style: Style) -> String {
+    if x.is_nan() {
+        return String::from("not a number");
+    }
+    // else
+    let spelled = if x.is_infinite() {
+        String::from("infinity")
+    } else {
+        let text = x.abs().to_string();
+        let (int_part, frac_part) = text.split_once('.').unwrap_or((&text, ""));
+        let mut words = vec![spell_digits(int_part, style)];
+        if !frac_part.is_empty() {
+            words.push(String::from("point"));
+        }
+        for c in frac_part.chars() {
+            words.push(length_1(c.to_digit(10).unwrap()));
+        }
+        words.join(" ")
+    };
+    if x < 0.0 {
+        with_sign(&spelled, style)
+    } else {
+        spelled
+    }
+}
+
+/// Returns the spelled version of the fraction `numerator / denominator`.
+///
+/// The denominator must be positive.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_fraction, Style};
+///
+/// assert_eq!(spell_fraction(2, 3, Style::British), "two thirds");
+/// assert_eq!(spell_fraction(1, 2, Style::British), "one half");
+/// assert_eq!(spell_fraction(3, 4, Style::British), "three quarters");
+/// assert_eq!(spell_fraction(-5, 100, Style::American), "negative five hundredths");
+/// ```
+pub fn spell_fraction(numerator: i64, denominator: u64, style: Style) -> String {
+    assert!(denominator > 0);
 
-    let digits = jmath::digits(n as u64);
-    match digits.len() {
-        1 => length_1(&digits),
-        2 => length_2(&digits),
-        3 => length_3(&digits),
-        4 => length_4(&digits),
-        _ => String::from("unknown"),
+    let count = spell_i64(numerator, style);
+    if denominator == 1 {
+        return count;
     }
+    // else
+    let plural = numerator.unsigned_abs() != 1;
+    let unit = match FRACTIONS.iter().find(|(d, _, _)| *d == denominator) {
+        Some((_, one, many)) => (if plural { *many } else { *one }).to_string(),
+        None => {
+            let ordinal = spell_ordinal(denominator, style);
+            // "one hundredth" -> "hundredth", but "one hundred and first" is kept
+            let ordinal = match ordinal.strip_prefix("one ") {
+                Some(rest) if !rest.contains(' ') => rest.to_string(),
+                _ => ordinal,
+            };
+            if plural {
+                format!("{}s", ordinal)
+            } else {
+                ordinal
+            }
+        }
+    };
+    format!("{} {}", count, unit)
+}
+
+/// A currency, with the names of its units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency {
+    pub major_one: &'static str,
+    pub major_many: &'static str,
+    pub minor_one: &'static str,
+    pub minor_many: &'static str,
+    /// How many minor units make a major unit (e.g. 100 cents make a dollar).
+    pub minor_per_major: u64,
+}
+
+/// US dollar
+pub const USD: Currency = Currency {
+    major_one: "dollar",
+    major_many: "dollars",
+    minor_one: "cent",
+    minor_many: "cents",
+    minor_per_major: 100,
+};
+
+/// euro
+pub const EUR: Currency = Currency {
+    major_one: "euro",
+    major_many: "euros",
+    minor_one: "cent",
+    minor_many: "cents",
+    minor_per_major: 100,
+};
+
+/// pound sterling
+pub const GBP: Currency = Currency {
+    major_one: "pound",
+    major_many: "pounds",
+    minor_one: "penny",
+    minor_many: "pence",
+    minor_per_major: 100,
+};
+
+/// Returns the spelled version of the given amount of money.
+///
+/// The amount is given in minor units (e.g. in cents), thus there's no rounding problem.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{spell_currency, Style, GBP, USD};
+///
+/// assert_eq!(spell_currency(1205, &USD, Style::British), "twelve dollars and five cents");
+/// assert_eq!(spell_currency(100, &USD, Style::British), "one dollar");
+/// assert_eq!(spell_currency(1, &GBP, Style::British), "one penny");
+/// assert_eq!(spell_currency(-250, &GBP, Style::American), "negative two pounds and fifty pence");
+/// ```
+pub fn spell_currency(amount: i64, currency: &Currency, style: Style) -> String {
+    let per = currency.minor_per_major.max(1);
+    let total = amount.unsigned_abs();
+    let (major, minor) = (total / per, total % per);
+    let unit = |n: u64, one: &str, many: &str| {
+        format!(
+            "{} {}",
+            spell_u64(n, style),
+            if n == 1 { one } else { many }
+        )
+    };
+
+    let minor_part = unit(minor, currency.minor_one, currency.minor_many);
+    let spelled = if major == 0 && minor > 0 {
+        minor_part
+    } else {
+        let major_part = unit(major, currency.major_one, currency.major_many);
+        if minor > 0 {
+            format!("{} and {}", major_part, minor_part)
+        } else {
+            major_part
+        }
+    };
+    if amount < 0 {
+        with_sign(&spelled, style)
+    } else {
+        spelled
+    }
+}
+
+/// Spells numbers in a given language.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{English, German, Hungarian, Speller};
+///
+/// let spellers: Vec<Box<dyn Speller>> =
+///     vec![Box::new(English::default()), Box::new(Hungarian), Box::new(German)];
+/// let spelled: Vec<String> = spellers.iter().map(|sp| sp.spell_i64(-21)).collect();
+///
+/// assert_eq!(spelled, ["minus twenty-one", "mínusz huszonegy", "minus einundzwanzig"]);
+/// ```
+pub trait Speller {
+    /// Spells the given non-negative number.
+    fn spell_u64(&self, n: u64) -> String;
+
+    /// The word put before negative numbers (e.g. "minus").
+    fn minus_word(&self) -> &str;
+
+    /// Spells the given (possibly negative) number.
+    fn spell_i64(&self, n: i64) -> String {
+        let spelled = self.spell_u64(n.unsigned_abs());
+        if n < 0 {
+            format!("{} {}", self.minus_word(), spelled)
+        } else {
+            spelled
+        }
+    }
+}
+
+/// English, with short-scale names (see [`spell_u64`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct English {
+    pub style: Style,
+}
+
+impl Speller for English {
+    fn spell_u64(&self, n: u64) -> String {
+        spell_u64(n, self.style)
+    }
+
+    fn minus_word(&self) -> &str {
+        match self.style {
+            Style::British => "minus",
+            Style::American => "negative",
+        }
+    }
+}
+
+/// Hungarian, with long-scale names (ezer, millió, milliárd, billió, ...).
+///
+/// The compounds are written in one word, e.g. "huszonegy" (21), "kétszáz" (200).
+/// In compounds "két" is used instead of "kettő" (e.g. "kétezer", but "huszonkettő").
+/// Above 2000, the groups of three digits are separated by hyphens
+/// (e.g. "ezerkilencszázkilencvenkilenc" (1999), but "kétezer-egy" (2001)).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{Hungarian, Speller};
+///
+/// assert_eq!(Hungarian.spell_u64(21), "huszonegy");
+/// assert_eq!(Hungarian.spell_u64(1848), "ezernyolcszáznegyvennyolc");
+/// assert_eq!(Hungarian.spell_u64(2022), "kétezer-huszonkettő");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Hungarian;
+
+impl Speller for Hungarian {
+    fn spell_u64(&self, n: u64) -> String {
+        if n == 0 {
+            return String::from("nulla");
+        }
+        // else
+        let groups = to_groups(&n.to_string());
+        let mut parts = vec![];
+        for (i, &group) in groups.iter().enumerate() {
+            if group == 0 {
+                continue;
+            }
+            let scale = HU_SCALES[groups.len() - 1 - i];
+            let part = if scale.is_empty() {
+                hu_below_1000(group, false)
+            } else if group == 1 && scale == "ezer" && parts.is_empty() {
+                scale.to_string() // "ezer", not "egyezer"
+            } else {
+                format!("{}{}", hu_below_1000(group, true), scale)
+            };
+            parts.push(part);
+        }
+        let sep = if n > 2000 { "-" } else { "" };
+        parts.join(sep)
+    }
+
+    fn minus_word(&self) -> &str {
+        "mínusz"
+    }
+}
+
+/// German, with long-scale names (Million, Milliarde, Billion, ...).
+///
+/// The numbers below a million are written in one word, with the units before
+/// the tens, e.g. "einundzwanzig" (21), "zweitausenddreihundertvier" (2304).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{German, Speller};
+///
+/// assert_eq!(German.spell_u64(21), "einundzwanzig");
+/// assert_eq!(German.spell_u64(101), "einhunderteins");
+/// assert_eq!(German.spell_u64(2_000_021), "zwei Millionen einundzwanzig");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct German;
+
+impl Speller for German {
+    fn spell_u64(&self, n: u64) -> String {
+        if n == 0 {
+            return String::from("null");
+        }
+        // else
+        let groups = to_groups(&n.to_string());
+        let mut words = vec![];
+        for (i, &group) in groups.iter().enumerate() {
+            let index = groups.len() - 1 - i; // 1000^index
+            if group == 0 || index < 2 {
+                continue;
+            }
+            let (one, many) = DE_SCALES[index - 2];
+            if group == 1 {
+                words.push(format!("eine {}", one));
+            } else {
+                // the scales are feminine: "einhunderteine Millionen"
+                let mut word = de_below_1000(group, false);
+                if group % 100 == 1 {
+                    word.push('e');
+                }
+                words.push(format!("{} {}", word, many));
+            }
+        }
+        let below_million = (n % 1_000_000) as u32;
+        if below_million > 0 {
+            let (high, low) = (below_million / 1000, below_million % 1000);
+            let mut word = String::new();
+            if high > 0 {
+                word.push_str(&de_below_1000(high, false));
+                word.push_str("tausend");
+            }
+            word.push_str(&de_below_1000(low, true));
+            words.push(word);
+        }
+        words.join(" ")
+    }
+
+    fn minus_word(&self) -> &str {
+        "minus"
+    }
+}
+
+/// The error returned by [`parse_number`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// There's no number in the text.
+    Empty,
+    /// A word that is not part of a number.
+    UnknownWord(String),
+    /// A number word in a wrong place (e.g. "two three", "thousand million").
+    UnexpectedWord(String),
+    /// The number doesn't fit in a `u64`.
+    Overflow,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "no number was found"),
+            ParseError::UnknownWord(word) => write!(f, "unknown word: {:?}", word),
+            ParseError::UnexpectedWord(word) => write!(f, "unexpected word: {:?}", word),
+            ParseError::Overflow => write!(f, "the number is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a spelled (written out in words) number. The inverse of [`spell_u64`].
+///
+/// It's lenient: the case doesn't matter, hyphens and spaces are interchangeable,
+/// "and" and commas are optional, the last word can be an ordinal ("forty-second"),
+/// "a" can stand for "one" ("a hundred"), and the groups can be given in digits ("3 million").
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jspell::{parse_number, ParseError};
+///
+/// assert_eq!(parse_number("three hundred and forty-two"), Ok(342));
+/// assert_eq!(parse_number("Three Hundred Forty Two"), Ok(342));
+/// assert_eq!(parse_number("forty-second"), Ok(42));
+/// assert_eq!(parse_number("one million, two hundred thousand"), Ok(1_200_000));
+/// assert_eq!(parse_number("twelve hundred"), Ok(1200));
+/// assert_eq!(parse_number("two three"), Err(ParseError::UnexpectedWord("three".to_string())));
+/// ```
+pub fn parse_number(text: &str) -> Result<u64, ParseError> {
+    let text = normalize(text);
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    let ordinal = words.last().and_then(|last| from_ordinal(last));
+    if let Some(cardinal) = &ordinal {
+        *words.last_mut().unwrap() = cardinal;
+    }
+    words.retain(|&word| word != "and");
+    if words.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    if words == ["zero"] {
+        return Ok(0);
+    }
+    // else
+
+    let mut total: u64 = 0; // the completed groups (thousands, millions, ...)
+    let mut current: u64 = 0; // the current group
+    let mut last = Token::Start;
+    let mut last_scale = u64::MAX;
+    for (i, &word) in words.iter().enumerate() {
+        let token = to_token(word)?;
+        let allowed = match token {
+            Token::Unit(_) => matches!(
+                last,
+                Token::Start | Token::Hundred | Token::Scale(_) | Token::Tens(_)
+            ),
+            Token::Teen(_) | Token::Tens(_) => {
+                matches!(last, Token::Start | Token::Hundred | Token::Scale(_))
+            }
+            // "one hundred 500" and "one thousand 5000" are not numbers
+            Token::Digits(n) => match last {
+                Token::Start => true,
+                Token::Hundred => n < 100,
+                Token::Scale(scale) => n < scale,
+                _ => false,
+            },
+            Token::A => {
+                let next = words.get(i + 1).map(|next| to_token(next));
+                matches!(last, Token::Start | Token::Scale(_))
+                    && matches!(next, Some(Ok(Token::Hundred | Token::Scale(_))))
+            }
+            Token::Hundred => current < 100 && !matches!(last, Token::Scale(_)),
+            Token::Scale(scale) => scale < last_scale && !matches!(last, Token::Scale(_)),
+            Token::Zero | Token::Start => false,
+        };
+        if !allowed {
+            return Err(ParseError::UnexpectedWord(word.to_string()));
+        }
+        match token {
+            Token::Unit(n) | Token::Teen(n) | Token::Tens(n) | Token::Digits(n) => {
+                current = current.checked_add(n).ok_or(ParseError::Overflow)?;
+            }
+            Token::A => current = 1,
+            Token::Hundred => current = current.max(1) * 100,
+            Token::Scale(scale) => {
+                let group = current
+                    .max(1)
+                    .checked_mul(scale)
+                    .ok_or(ParseError::Overflow)?;
+                total = total.checked_add(group).ok_or(ParseError::Overflow)?;
+                current = 0;
+                last_scale = scale;
+            }
+            Token::Zero | Token::Start => unreachable!(),
+        }
+        last = token;
+    }
+    total.checked_add(current).ok_or(ParseError::Overflow)
 }
 
 /**********
   private
 ***********/
 
-static NUMBERS: Lazy<HashMap<i32, &str>> = Lazy::new(|| {
+static NUMBERS: Lazy<HashMap<u32, &str>> = Lazy::new(|| {
     HashMap::from([
         (0, "zero"),
         (1, "one"),
@@ -67,55 +592,378 @@ static NUMBERS: Lazy<HashMap<i32, &str>> = Lazy::new(|| {
         (70, "seventy"),
         (80, "eighty"),
         (90, "ninety"),
-        (100, "one hundred"),
-        (1000, "one thousand"),
     ])
 });
 
-fn length_1(digits: &[i32]) -> String {
-    NUMBERS.get(&digits[0]).unwrap().to_string()
+/// The ordinals that are not simply "...th" / "...ieth".
+static ORDINALS: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
+    HashMap::from([
+        ("one", "first"),
+        ("two", "second"),
+        ("three", "third"),
+        ("five", "fifth"),
+        ("eight", "eighth"),
+        ("nine", "ninth"),
+        ("twelve", "twelfth"),
+    ])
+});
+
+/// Denominators with their own names: (denominator, singular, plural).
+const FRACTIONS: [(u64, &str, &str); 2] = [(2, "half", "halves"), (4, "quarter", "quarters")];
+
+/// Short-scale names; `SCALES[i]` belongs to 1000^i.
+const SCALES: [&str; 22] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+    "sextillion",
+    "septillion",
+    "octillion",
+    "nonillion",
+    "decillion",
+    "undecillion",
+    "duodecillion",
+    "tredecillion",
+    "quattuordecillion",
+    "quindecillion",
+    "sexdecillion",
+    "septendecillion",
+    "octodecillion",
+    "novemdecillion",
+    "vigintillion",
+];
+
+fn with_sign(spelled: &str, style: Style) -> String {
+    match style {
+        Style::British => format!("minus {}", spelled),
+        Style::American => format!("negative {}", spelled),
+    }
 }
 
-fn length_2(digits: &[i32]) -> String {
-    let n = digits[0] * 10 + digits[1];
-    if digits[0] == 1 {
-        NUMBERS.get(&n).unwrap().to_string()
-    } else {
-        if digits[1] == 0 {
-            return NUMBERS.get(&n).unwrap().to_string();
+/// Turns a spelled cardinal into an ordinal (only the last word changes).
+fn to_ordinal(cardinal: &str) -> String {
+    // "forty-two" -> "forty-second"
+    let split = cardinal.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match ORDINALS.get(last) {
+        Some(ordinal) => ordinal.to_string(),
+        None => match last.strip_suffix('y') {
+            Some(stem) => format!("{}ieth", stem), // "twenty" -> "twentieth"
+            None => format!("{}th", last),
+        },
+    };
+    format!("{}{}", head, last)
+}
+
+/// Spells a non-negative number given in decimal.
+fn spell_digits(digits: &str, style: Style) -> String {
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return length_1(0);
+    }
+    // else
+    let top = 3 * (SCALES.len() - 1); // number of digits below the largest scale
+    if digits.len() <= top + 3 {
+        return spell_groups(&to_groups(digits), false, style).join(" ");
+    }
+    // else, too big: "<high> vigintillion <low>"
+    let (high, low) = digits.split_at(digits.len() - top);
+    let mut words = vec![
+        spell_digits(high, style),
+        SCALES[SCALES.len() - 1].to_string(),
+    ];
+    words.extend(spell_groups(&to_groups(low), true, style));
+    words.join(" ")
+}
+
+/// Splits a number (given in decimal) into groups of 3 digits, the most significant first.
+fn to_groups(digits: &str) -> Vec<u32> {
+    let bytes = digits.as_bytes();
+    let first = match bytes.len() % 3 {
+        0 => 3,
+        rest => rest,
+    };
+    let mut groups = vec![&bytes[..first]];
+    groups.extend(bytes[first..].chunks(3));
+    groups
+        .iter()
+        .map(|group| group.iter().fold(0, |acc, &b| acc * 10 + (b - b'0') as u32))
+        .collect()
+}
+
+/// Spells the groups of 3 digits (the most significant first), skipping the zero groups.
+///
+/// `has_higher` tells if there's a non-zero part above these groups.
+fn spell_groups(groups: &[u32], has_higher: bool, style: Style) -> Vec<String> {
+    let mut words = vec![];
+    let mut has_higher = has_higher;
+    for (i, &group) in groups.iter().enumerate() {
+        if group == 0 {
+            continue;
         }
-        // else
-        let n = digits[0] * 10;
-        format!(
-            "{}-{}",
-            NUMBERS.get(&n).unwrap(),
-            NUMBERS.get(&digits[1]).unwrap()
-        )
+        let scale = SCALES[groups.len() - 1 - i];
+        let is_last = scale.is_empty();
+        if is_last && has_higher && group < 100 && style == Style::British {
+            words.push("and".to_string());
+        }
+        words.push(length_3(group, style));
+        if !is_last {
+            words.push(scale.to_string());
+        }
+        has_higher = true;
     }
+    words
 }
 
-fn length_3(digits: &[i32]) -> String {
-    let head = digits[0];
-    let tail = digits[1] * 10 + digits[2];
-    if tail == 0 {
-        // "00"
-        format!("{} hundred", length_1(&[head]))
-    } else if tail < 10 {
-        // "01", "02", ..., "09"
-        format!("{} hundred and {}", length_1(&[head]), length_1(&[tail]))
+/// 0 <= n < 10
+fn length_1(n: u32) -> String {
+    NUMBERS.get(&n).unwrap().to_string()
+}
+
+/// 0 <= n < 100
+fn length_2(n: u32) -> String {
+    if n < 20 || n.is_multiple_of(10) {
+        return NUMBERS.get(&n).unwrap().to_string();
+    }
+    // else
+    format!(
+        "{}-{}",
+        NUMBERS.get(&(n / 10 * 10)).unwrap(),
+        length_1(n % 10)
+    )
+}
+
+/// 0 < n < 1000
+fn length_3(n: u32, style: Style) -> String {
+    let (head, tail) = (n / 100, n % 100);
+    if head == 0 {
+        return length_2(tail);
+    }
+    // else
+    match (tail, style) {
+        (0, _) => format!("{} hundred", length_1(head)),
+        (_, Style::British) => format!("{} hundred and {}", length_1(head), length_2(tail)),
+        (_, Style::American) => format!("{} hundred {}", length_1(head), length_2(tail)),
+    }
+}
+
+// Parsing
+
+/// A word of a spelled number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Start, // before the first word
+    Zero,
+    Unit(u64),   // 1..=9
+    Teen(u64),   // 10..=19
+    Tens(u64),   // 20, 30, ..., 90
+    Digits(u64), // a number given in digits
+    A,           // "a" (as in "a hundred")
+    Hundred,
+    Scale(u64), // 1000, 1000^2, ...
+}
+
+fn to_token(word: &str) -> Result<Token, ParseError> {
+    if let Some((&n, _)) = NUMBERS.iter().find(|(_, &w)| w == word) {
+        let n = n as u64;
+        return Ok(match n {
+            0 => Token::Zero,
+            1..=9 => Token::Unit(n),
+            10..=19 => Token::Teen(n),
+            _ => Token::Tens(n),
+        });
+    }
+    if let Some(i) = SCALES.iter().skip(1).position(|&scale| scale == word) {
+        let scale = 1000_u64
+            .checked_pow(i as u32 + 1)
+            .ok_or(ParseError::Overflow)?;
+        return Ok(Token::Scale(scale));
+    }
+    match word {
+        "a" => Ok(Token::A),
+        "hundred" => Ok(Token::Hundred),
+        _ if word.bytes().all(|b| b.is_ascii_digit()) => match word.parse() {
+            Ok(n) => Ok(Token::Digits(n)),
+            Err(_) => Err(ParseError::Overflow),
+        },
+        _ => Err(ParseError::UnknownWord(word.to_string())),
+    }
+}
+
+/// Lowercases the text and replaces the hyphens and commas with spaces. A comma
+/// between digits is a thousands separator, thus it's removed (e.g. "1,000" -> "1000").
+fn normalize(text: &str) -> String {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            ',' if i > 0
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) => {}
+            '-' | ',' => result.push(' '),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Turns an ordinal word into a cardinal word, e.g. "second" -> "two", "twentieth" -> "twenty",
+/// "42nd" -> "42". Returns `None` if the word is not an ordinal.
+fn from_ordinal(word: &str) -> Option<String> {
+    if let Some((&cardinal, _)) = ORDINALS.iter().find(|(_, &ordinal)| ordinal == word) {
+        return Some(cardinal.to_string());
+    }
+    for suffix in ["st", "nd", "rd", "th"] {
+        if let Some(digits) = word.strip_suffix(suffix) {
+            if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Some(digits.to_string());
+            }
+        }
+    }
+    if let Some(stem) = word.strip_suffix("ieth") {
+        let cardinal = format!("{}y", stem);
+        if NUMBERS.values().any(|&w| w == cardinal) {
+            return Some(cardinal);
+        }
+    }
+    let cardinal = word.strip_suffix("th")?;
+    let is_number_word = NUMBERS.values().any(|&w| w == cardinal)
+        || cardinal == "hundred"
+        || SCALES.contains(&cardinal);
+    is_number_word.then(|| cardinal.to_string())
+}
+
+// Hungarian
+
+const HU_ONES: [&str; 10] = [
+    "", "egy", "kettő", "három", "négy", "öt", "hat", "hét", "nyolc", "kilenc",
+];
+
+/// The tens alone ("húsz") and before units ("huszon-").
+const HU_TENS: [(&str, &str); 10] = [
+    ("", ""),
+    ("tíz", "tizen"),
+    ("húsz", "huszon"),
+    ("harminc", "harminc"),
+    ("negyven", "negyven"),
+    ("ötven", "ötven"),
+    ("hatvan", "hatvan"),
+    ("hetven", "hetven"),
+    ("nyolcvan", "nyolcvan"),
+    ("kilencven", "kilencven"),
+];
+
+/// Long-scale names; `HU_SCALES[i]` belongs to 1000^i.
+const HU_SCALES: [&str; 7] = [
+    "",
+    "ezer",
+    "millió",
+    "milliárd",
+    "billió",
+    "billiárd",
+    "trillió",
+];
+
+/// 0 < n < 1000. If `compound` is true, a scale word follows, thus "kettő" becomes "két".
+fn hu_below_1000(n: u32, compound: bool) -> String {
+    let unit = |u: u32, compound: bool| {
+        if u == 2 && compound {
+            "két"
+        } else {
+            HU_ONES[u as usize]
+        }
+    };
+    let (hundreds, tens, units) = (n / 100, n / 10 % 10, n % 10);
+    let mut result = String::new();
+    if hundreds > 1 {
+        result.push_str(unit(hundreds, true));
+    }
+    if hundreds > 0 {
+        result.push_str("száz");
+    }
+    let (alone, prefix) = HU_TENS[tens as usize];
+    if units == 0 {
+        result.push_str(alone);
     } else {
-        let tail_digits = jmath::digits(tail as u64);
-        format!(
-            "{} hundred and {}",
-            length_1(&[head]),
-            length_2(&tail_digits)
-        )
+        result.push_str(prefix);
+        result.push_str(unit(units, compound));
     }
+    result
 }
 
-fn length_4(digits: &[i32]) -> String {
-    let n = digits[0] * 1000 + digits[1] * 100 + digits[2] * 10 + digits[3];
-    NUMBERS.get(&n).unwrap().to_string()
+// German
+
+const DE_ONES: [&str; 20] = [
+    "",
+    "eins",
+    "zwei",
+    "drei",
+    "vier",
+    "fünf",
+    "sechs",
+    "sieben",
+    "acht",
+    "neun",
+    "zehn",
+    "elf",
+    "zwölf",
+    "dreizehn",
+    "vierzehn",
+    "fünfzehn",
+    "sechzehn",
+    "siebzehn",
+    "achtzehn",
+    "neunzehn",
+];
+
+const DE_TENS: [&str; 10] = [
+    "", "zehn", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig",
+    "neunzig",
+];
+
+/// Long-scale names (singular, plural) from the million; `DE_SCALES[i]` belongs to 1000^(i+2).
+const DE_SCALES: [(&str, &str); 5] = [
+    ("Million", "Millionen"),
+    ("Milliarde", "Milliarden"),
+    ("Billion", "Billionen"),
+    ("Billiarde", "Billiarden"),
+    ("Trillion", "Trillionen"),
+];
+
+/// 0 <= n < 1000. If `last` is false, something follows (e.g. "tausend"),
+/// thus "eins" becomes "ein".
+fn de_below_1000(n: u32, last: bool) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let mut result = String::new();
+    if hundreds > 0 {
+        result.push_str(if hundreds == 1 {
+            "ein"
+        } else {
+            DE_ONES[hundreds as usize]
+        });
+        result.push_str("hundert");
+    }
+    if rest == 1 && !last {
+        result.push_str("ein");
+    } else if rest < 20 {
+        result.push_str(DE_ONES[rest as usize]);
+    } else {
+        let (tens, units) = (rest / 10, rest % 10);
+        if units > 0 {
+            result.push_str(if units == 1 {
+                "ein"
+            } else {
+                DE_ONES[units as usize]
+            });
+            result.push_str("und");
+        }
+        result.push_str(DE_TENS[tens as usize]);
+    }
+    result
 }
 
 // ==========================================================================
@@ -171,4 +1019,441 @@ mod tests {
         sb = sb.replace(" ", "").replace("-", "");
         assert_eq!(sb.len(), 21124);
     }
+
+    #[test]
+    fn spell_u64_test() {
+        let british = |n| spell_u64(n, Style::British);
+        let american = |n| spell_u64(n, Style::American);
+        //
+        assert_eq!(british(1001), "one thousand and one");
+        assert_eq!(american(1001), "one thousand one");
+        assert_eq!(british(1100), "one thousand one hundred");
+        assert_eq!(british(1999), "one thousand nine hundred and ninety-nine");
+        assert_eq!(american(1999), "one thousand nine hundred ninety-nine");
+        assert_eq!(british(20_000), "twenty thousand");
+        assert_eq!(british(100_005), "one hundred thousand and five");
+        assert_eq!(british(1_000_000), "one million");
+        assert_eq!(british(1_002_000), "one million two thousand");
+        assert_eq!(
+            british(123_456_789),
+            "one hundred and twenty-three million four hundred and fifty-six thousand seven hundred and eighty-nine"
+        );
+        assert_eq!(british(1_000_000_000_000), "one trillion");
+        assert_eq!(
+            american(u64::MAX),
+            "eighteen quintillion four hundred forty-six quadrillion seven hundred forty-four trillion \
+seventy-three billion seven hundred nine million five hundred fifty-one thousand six hundred fifteen"
+        );
+    }
+
+    #[test]
+    fn spell_i64_test() {
+        assert_eq!(spell_i64(0, Style::British), "zero");
+        assert_eq!(spell_i64(42, Style::British), "forty-two");
+        assert_eq!(spell_i64(-42, Style::British), "minus forty-two");
+        assert_eq!(spell_i64(-42, Style::American), "negative forty-two");
+        assert_eq!(
+            spell_i64(i64::MIN, Style::American),
+            "negative nine quintillion two hundred twenty-three quadrillion three hundred seventy-two trillion \
+thirty-six billion eight hundred fifty-four million seven hundred seventy-five thousand eight hundred eight"
+        );
+    }
+
+    #[test]
+    fn spell_bigint_test() {
+        let spell = |s: &str, style| spell_bigint(&s.parse::<BigInt>().unwrap(), style);
+        //
+        assert_eq!(spell("0", Style::British), "zero");
+        assert_eq!(spell("-0", Style::British), "zero");
+        assert_eq!(spell("-7", Style::American), "negative seven");
+        assert_eq!(
+            spell("1000000000000000000000", Style::British),
+            "one sextillion"
+        );
+        let vigintillion = format!("1{}", "0".repeat(63));
+        assert_eq!(spell(&vigintillion, Style::British), "one vigintillion");
+        let n = format!("999{}", "0".repeat(63));
+        assert_eq!(
+            spell(&n, Style::British),
+            "nine hundred and ninety-nine vigintillion"
+        );
+        let n = format!("1{}", "0".repeat(66));
+        assert_eq!(spell(&n, Style::British), "one thousand vigintillion");
+        let n = format!("1002{}5", "0".repeat(62));
+        assert_eq!(
+            spell(&n, Style::British),
+            "one thousand and two vigintillion and five"
+        );
+        assert_eq!(
+            spell(&n, Style::American),
+            "one thousand two vigintillion five"
+        );
+        let n = format!("1{}", "0".repeat(126));
+        assert_eq!(spell(&n, Style::American), "one vigintillion vigintillion");
+    }
+
+    /// The number of letters (spaces, hyphens, etc. are not counted).
+    fn letters(s: &str) -> usize {
+        s.chars().filter(|c| c.is_alphabetic()).count()
+    }
+
+    #[test]
+    fn spell_ordinal_test1() {
+        assert_eq!(spell_ordinal(0, Style::British), "zeroth");
+        assert_eq!(spell_ordinal(1, Style::British), "first");
+        assert_eq!(spell_ordinal(2, Style::British), "second");
+        assert_eq!(spell_ordinal(3, Style::British), "third");
+        assert_eq!(spell_ordinal(4, Style::British), "fourth");
+        assert_eq!(spell_ordinal(5, Style::British), "fifth");
+        assert_eq!(spell_ordinal(8, Style::British), "eighth");
+        assert_eq!(spell_ordinal(9, Style::British), "ninth");
+        assert_eq!(spell_ordinal(11, Style::British), "eleventh");
+        assert_eq!(spell_ordinal(12, Style::British), "twelfth");
+        assert_eq!(spell_ordinal(20, Style::British), "twentieth");
+        assert_eq!(spell_ordinal(42, Style::British), "forty-second");
+        assert_eq!(spell_ordinal(100, Style::British), "one hundredth");
+        assert_eq!(spell_ordinal(101, Style::British), "one hundred and first");
+        assert_eq!(spell_ordinal(101, Style::American), "one hundred first");
+        assert_eq!(
+            spell_ordinal(1_000_000_000, Style::British),
+            "one billionth"
+        );
+    }
+
+    #[test]
+    fn spell_ordinal_test2() {
+        let total: usize = (1..1000 + 1)
+            .map(|n| letters(&spell_ordinal(n, Style::British)))
+            .sum();
+        assert_eq!(total, 22834);
+    }
+
+    #[test]
+    fn spell_decimal_test1() {
+        assert_eq!(spell_decimal(0.0, Style::British), "zero");
+        assert_eq!(spell_decimal(-0.0, Style::British), "zero");
+        assert_eq!(spell_decimal(0.1, Style::British), "zero point one");
+        assert_eq!(spell_decimal(1.05, Style::British), "one point zero five");
+        assert_eq!(spell_decimal(-2.5, Style::British), "minus two point five");
+        assert_eq!(
+            spell_decimal(-2.5, Style::American),
+            "negative two point five"
+        );
+        assert_eq!(spell_decimal(1e6, Style::British), "one million");
+        assert_eq!(spell_decimal(f64::INFINITY, Style::British), "infinity");
+        assert_eq!(
+            spell_decimal(f64::NEG_INFINITY, Style::British),
+            "minus infinity"
+        );
+        assert_eq!(spell_decimal(f64::NAN, Style::British), "not a number");
+    }
+
+    #[test]
+    fn spell_decimal_test2() {
+        let total: usize = (0..1000 + 1)
+            .map(|k| letters(&spell_decimal(k as f64 / 100.0, Style::British)))
+            .sum();
+        assert_eq!(total, 16513);
+    }
+
+    #[test]
+    fn spell_fraction_test1() {
+        assert_eq!(spell_fraction(1, 2, Style::British), "one half");
+        assert_eq!(spell_fraction(3, 2, Style::British), "three halves");
+        assert_eq!(spell_fraction(1, 3, Style::British), "one third");
+        assert_eq!(spell_fraction(2, 3, Style::British), "two thirds");
+        assert_eq!(spell_fraction(1, 4, Style::British), "one quarter");
+        assert_eq!(spell_fraction(0, 5, Style::British), "zero fifths");
+        assert_eq!(spell_fraction(-1, 8, Style::British), "minus one eighth");
+        assert_eq!(spell_fraction(7, 1, Style::British), "seven");
+        assert_eq!(spell_fraction(3, 100, Style::British), "three hundredths");
+        assert_eq!(spell_fraction(1, 1000, Style::British), "one thousandth");
+        assert_eq!(
+            spell_fraction(1, 101, Style::British),
+            "one one hundred and first"
+        );
+        assert_eq!(
+            spell_fraction(5, 22, Style::American),
+            "five twenty-seconds"
+        );
+    }
+
+    #[test]
+    fn spell_fraction_test2() {
+        let mut total = 0;
+        for d in 2..12 + 1 {
+            for n in 1..d {
+                total += letters(&spell_fraction(n as i64, d, Style::British));
+            }
+        }
+        assert_eq!(total, 716);
+    }
+
+    #[test]
+    #[should_panic]
+    fn spell_fraction_zero_denominator_test() {
+        spell_fraction(1, 0, Style::British);
+    }
+
+    #[test]
+    fn spell_currency_test1() {
+        assert_eq!(spell_currency(0, &USD, Style::British), "zero dollars");
+        assert_eq!(spell_currency(1, &USD, Style::British), "one cent");
+        assert_eq!(spell_currency(5, &USD, Style::British), "five cents");
+        assert_eq!(spell_currency(100, &USD, Style::British), "one dollar");
+        assert_eq!(
+            spell_currency(101, &USD, Style::British),
+            "one dollar and one cent"
+        );
+        assert_eq!(
+            spell_currency(1205, &USD, Style::British),
+            "twelve dollars and five cents"
+        );
+        assert_eq!(
+            spell_currency(-1205, &USD, Style::American),
+            "negative twelve dollars and five cents"
+        );
+        assert_eq!(
+            spell_currency(199, &EUR, Style::British),
+            "one euro and ninety-nine cents"
+        );
+        assert_eq!(
+            spell_currency(201, &GBP, Style::British),
+            "two pounds and one penny"
+        );
+        assert_eq!(
+            spell_currency(10_500, &GBP, Style::American),
+            "one hundred five pounds"
+        );
+        let yen = Currency {
+            major_one: "yen",
+            major_many: "yen",
+            minor_one: "",
+            minor_many: "",
+            minor_per_major: 1,
+        };
+        assert_eq!(
+            spell_currency(1500, &yen, Style::British),
+            "one thousand five hundred yen"
+        );
+    }
+
+    #[test]
+    fn spell_currency_test2() {
+        let total: usize = (0..100_000 + 1)
+            .map(|n| letters(&spell_currency(n, &USD, Style::British)))
+            .sum();
+        assert_eq!(total, 4455232);
+    }
+
+    #[test]
+    fn english_speller_test() {
+        let british = English::default();
+        let american = English {
+            style: Style::American,
+        };
+        assert_eq!(british.spell_u64(115), "one hundred and fifteen");
+        assert_eq!(american.spell_u64(115), "one hundred fifteen");
+        assert_eq!(british.spell_i64(-3), "minus three");
+        assert_eq!(american.spell_i64(-3), "negative three");
+    }
+
+    #[test]
+    fn hungarian_speller_test() {
+        let hu = |n| Hungarian.spell_u64(n);
+        assert_eq!(hu(0), "nulla");
+        assert_eq!(hu(1), "egy");
+        assert_eq!(hu(2), "kettő");
+        assert_eq!(hu(10), "tíz");
+        assert_eq!(hu(11), "tizenegy");
+        assert_eq!(hu(12), "tizenkettő");
+        assert_eq!(hu(20), "húsz");
+        assert_eq!(hu(21), "huszonegy");
+        assert_eq!(hu(22), "huszonkettő");
+        assert_eq!(hu(30), "harminc");
+        assert_eq!(hu(99), "kilencvenkilenc");
+        assert_eq!(hu(100), "száz");
+        assert_eq!(hu(101), "százegy");
+        assert_eq!(hu(200), "kétszáz");
+        assert_eq!(hu(999), "kilencszázkilencvenkilenc");
+        assert_eq!(hu(1000), "ezer");
+        assert_eq!(hu(1100), "ezerszáz");
+        assert_eq!(hu(1999), "ezerkilencszázkilencvenkilenc");
+        assert_eq!(hu(2000), "kétezer");
+        assert_eq!(hu(2001), "kétezer-egy");
+        assert_eq!(hu(2002), "kétezer-kettő");
+        assert_eq!(hu(22_222), "huszonkétezer-kétszázhuszonkettő");
+        assert_eq!(hu(100_000), "százezer");
+        assert_eq!(hu(1_000_000), "egymillió");
+        assert_eq!(hu(1_001_000), "egymillió-egyezer");
+        assert_eq!(hu(2_500_000), "kétmillió-ötszázezer");
+        assert_eq!(hu(3_000_000_007), "hárommilliárd-hét");
+        assert_eq!(hu(1_000_000_000_000), "egybillió");
+        assert_eq!(
+            hu(u64::MAX),
+            "tizennyolctrillió-négyszáznegyvenhatbilliárd-hétszáznegyvennégybillió-\
+hetvenhárommilliárd-hétszázkilencmillió-ötszázötvenegyezer-hatszáztizenöt"
+        );
+        assert_eq!(Hungarian.spell_i64(-2), "mínusz kettő");
+    }
+
+    #[test]
+    fn german_speller_test() {
+        let de = |n| German.spell_u64(n);
+        assert_eq!(de(0), "null");
+        assert_eq!(de(1), "eins");
+        assert_eq!(de(11), "elf");
+        assert_eq!(de(16), "sechzehn");
+        assert_eq!(de(17), "siebzehn");
+        assert_eq!(de(20), "zwanzig");
+        assert_eq!(de(21), "einundzwanzig");
+        assert_eq!(de(30), "dreißig");
+        assert_eq!(de(99), "neunundneunzig");
+        assert_eq!(de(100), "einhundert");
+        assert_eq!(de(101), "einhunderteins");
+        assert_eq!(de(111), "einhundertelf");
+        assert_eq!(de(1000), "eintausend");
+        assert_eq!(de(1001), "eintausendeins");
+        assert_eq!(de(2304), "zweitausenddreihundertvier");
+        assert_eq!(de(21_000), "einundzwanzigtausend");
+        assert_eq!(de(101_000), "einhunderteintausend");
+        assert_eq!(
+            de(999_999),
+            "neunhundertneunundneunzigtausendneunhundertneunundneunzig"
+        );
+        assert_eq!(de(1_000_000), "eine Million");
+        assert_eq!(de(2_000_001), "zwei Millionen eins");
+        assert_eq!(de(1_000_000_000), "eine Milliarde");
+        assert_eq!(de(101_000_000), "einhunderteine Millionen");
+        assert_eq!(de(1_001_000_000), "eine Milliarde eine Million");
+        assert_eq!(de(21_000_000), "einundzwanzig Millionen");
+        assert_eq!(de(301_000_000_000), "dreihunderteine Milliarden");
+        assert_eq!(de(5_001_000_000), "fünf Milliarden eine Million");
+        assert_eq!(de(1_000_000_000_000), "eine Billion");
+        assert_eq!(German.spell_i64(-1), "minus eins");
+    }
+
+    #[test]
+    fn parse_number_test1() {
+        assert_eq!(parse_number("zero"), Ok(0));
+        assert_eq!(parse_number("five"), Ok(5));
+        assert_eq!(parse_number("forty-two"), Ok(42));
+        assert_eq!(parse_number("forty two"), Ok(42));
+        assert_eq!(parse_number("  FORTY-Two  "), Ok(42));
+        assert_eq!(parse_number("one hundred"), Ok(100));
+        assert_eq!(parse_number("hundred"), Ok(100));
+        assert_eq!(parse_number("a hundred and five"), Ok(105));
+        assert_eq!(parse_number("three hundred and forty-two"), Ok(342));
+        assert_eq!(parse_number("three hundred forty-two"), Ok(342));
+        assert_eq!(parse_number("nineteen hundred and eighty-four"), Ok(1984));
+        assert_eq!(parse_number("one thousand and one"), Ok(1001));
+        assert_eq!(parse_number("a thousand"), Ok(1000));
+        assert_eq!(
+            parse_number("one million, two hundred thousand and three"),
+            Ok(1_200_003)
+        );
+        assert_eq!(parse_number("3 million 250 thousand"), Ok(3_250_000));
+        assert_eq!(parse_number("1,000,000"), Ok(1_000_000));
+        assert_eq!(parse_number("two billion"), Ok(2_000_000_000));
+        assert_eq!(
+            parse_number("seven quintillion"),
+            Ok(7_000_000_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn parse_number_test2() {
+        // ordinals
+        assert_eq!(parse_number("zeroth"), Ok(0));
+        assert_eq!(parse_number("first"), Ok(1));
+        assert_eq!(parse_number("Second"), Ok(2));
+        assert_eq!(parse_number("fifth"), Ok(5));
+        assert_eq!(parse_number("twelfth"), Ok(12));
+        assert_eq!(parse_number("nineteenth"), Ok(19));
+        assert_eq!(parse_number("twentieth"), Ok(20));
+        assert_eq!(parse_number("forty-second"), Ok(42));
+        assert_eq!(parse_number("one hundredth"), Ok(100));
+        assert_eq!(parse_number("one hundred and first"), Ok(101));
+        assert_eq!(parse_number("two millionth"), Ok(2_000_000));
+        assert_eq!(parse_number("42nd"), Ok(42));
+        assert_eq!(parse_number("3rd"), Ok(3));
+    }
+
+    #[test]
+    fn parse_number_errors_test() {
+        let unknown = |s: &str| Err(ParseError::UnknownWord(s.to_string()));
+        let unexpected = |s: &str| Err(ParseError::UnexpectedWord(s.to_string()));
+        //
+        assert_eq!(parse_number(""), Err(ParseError::Empty));
+        assert_eq!(parse_number(" and "), Err(ParseError::Empty));
+        assert_eq!(parse_number("forty-two apples"), unknown("apples"));
+        assert_eq!(parse_number("mouth"), unknown("mouth"));
+        assert_eq!(parse_number("minus five"), unknown("minus"));
+        assert_eq!(parse_number("two three"), unexpected("three"));
+        assert_eq!(parse_number("twenty ten"), unexpected("ten"));
+        assert_eq!(parse_number("five hundred hundred"), unexpected("hundred"));
+        assert_eq!(parse_number("one thousand million"), unexpected("million"));
+        assert_eq!(
+            parse_number("one thousand two thousand"),
+            unexpected("thousand")
+        );
+        assert_eq!(parse_number("zero five"), unexpected("zero"));
+        assert_eq!(parse_number("five zero"), unexpected("zero"));
+        assert_eq!(parse_number("a five"), unexpected("a"));
+        assert_eq!(parse_number("first five"), unknown("first"));
+        assert_eq!(parse_number("one sextillion"), Err(ParseError::Overflow));
+        assert_eq!(
+            parse_number("twenty quintillion"),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(
+            parse_number("99999999999999999999"),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(
+            parse_number("one hundred 18446744073709551615"),
+            unexpected("18446744073709551615")
+        );
+        assert_eq!(parse_number("one hundred 500"), unexpected("500"));
+        assert_eq!(parse_number("one hundred 100"), unexpected("100"));
+        assert_eq!(parse_number("one hundred 99"), Ok(199));
+        assert_eq!(
+            parse_number("18446744073709551 thousand 616"),
+            Err(ParseError::Overflow)
+        );
+        assert_eq!(parse_number("18446744073709551 thousand 615"), Ok(u64::MAX));
+        assert_eq!(parse_number("one thousand 5000"), unexpected("5000"));
+        assert_eq!(parse_number("one million 5000000"), unexpected("5000000"));
+        assert_eq!(parse_number("one million 999999"), Ok(1_999_999));
+        assert_eq!(parse_number("fooieth"), unknown("fooieth"));
+        assert_eq!(parse_number("twentieth"), Ok(20));
+        assert_eq!(
+            ParseError::UnknownWord("x".to_string()).to_string(),
+            "unknown word: \"x\""
+        );
+    }
+
+    #[test]
+    fn parse_number_roundtrip_test() {
+        let numbers = (0..2000).chain((0..64).map(|i| 1 << i)).chain([
+            1_000_042,
+            123_456_789,
+            9_876_543_210_123,
+            u64::MAX,
+        ]);
+        for n in numbers {
+            for style in [Style::British, Style::American] {
+                assert_eq!(parse_number(&spell_u64(n, style)), Ok(n));
+                assert_eq!(parse_number(&spell_ordinal(n, style)), Ok(n));
+            }
+        }
+    }
+
+    #[test]
+    fn to_groups_test() {
+        assert_eq!(to_groups("5"), [5]);
+        assert_eq!(to_groups("123"), [123]);
+        assert_eq!(to_groups("1234"), [1, 234]);
+        assert_eq!(to_groups("12000007"), [12, 0, 7]);
+    }
 }
@@ -1,7 +1,20 @@
 //! string
 
+use std::collections::HashMap;
+use std::fmt;
+
+use unicode_general_category::{get_general_category, GeneralCategory};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::jvec;
+
 /// Returns the reversed version of the input string.
 ///
+/// The string is reversed by grapheme clusters (user-perceived characters), thus
+/// letters with combining marks and emoji sequences stay intact.
+///
 /// # Examples
 ///
 /// ```
@@ -9,13 +22,19 @@
 /// let answer = jabba_lib::jstring::str_rev(text);
 ///
 /// assert_eq!(answer, "dcba");
+///
+/// assert_eq!(jabba_lib::jstring::str_rev("ne\u{301}e"), "ee\u{301}n");
+/// assert_eq!(jabba_lib::jstring::str_rev("1👨‍👩‍👧"), "👨‍👩‍👧1");
 /// ```
 pub fn str_rev(s: &str) -> String {
-    s.chars().rev().collect::<String>()
+    s.graphemes(true).rev().collect::<String>()
 }
 
 /// Returns `true` if the given string is palindrome.
 ///
+/// The comparison is exact (it's case-sensitive, etc.). For a looser check,
+/// see [`is_palindrome_with`].
+///
 /// # Examples
 ///
 /// ```
@@ -29,6 +48,64 @@ pub fn is_palindrome(s: &str) -> bool {
     s == str_rev(s)
 }
 
+/// Options for [`is_palindrome_with`]. By default, nothing is ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PalindromeOptions {
+    /// Compare the letters case-insensitively (see [`casefold`]).
+    pub ignore_case: bool,
+    /// Skip everything that is not a letter or a digit (punctuation, whitespace, etc.).
+    pub ignore_punctuation: bool,
+    /// Remove the accents (e.g. "é" is treated as "e").
+    pub ignore_diacritics: bool,
+}
+
+impl PalindromeOptions {
+    /// Ignores the case, the punctuation and the diacritics.
+    pub fn loose() -> Self {
+        PalindromeOptions {
+            ignore_case: true,
+            ignore_punctuation: true,
+            ignore_diacritics: true,
+        }
+    }
+}
+
+/// Returns `true` if the given string is palindrome, optionally ignoring
+/// the case, the punctuation and the diacritics.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jstring::{self, PalindromeOptions};
+///
+/// let text = "A man, a plan, a canal: Panamá!";
+///
+/// assert_eq!(jstring::is_palindrome(text), false);
+/// assert_eq!(jstring::is_palindrome_with(text, PalindromeOptions::loose()), true);
+///
+/// let options = PalindromeOptions {
+///     ignore_case: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(jstring::is_palindrome_with("Anna", options), true);
+/// ```
+pub fn is_palindrome_with(s: &str, options: PalindromeOptions) -> bool {
+    let mut text: String = if options.ignore_diacritics {
+        s.nfd()
+            .filter(|&c| get_general_category(c) != GeneralCategory::NonspacingMark)
+            .collect()
+    } else {
+        s.nfc().collect()
+    };
+    if options.ignore_case {
+        text = casefold(&text);
+    }
+    if options.ignore_punctuation {
+        text.retain(char::is_alphanumeric);
+    }
+    is_palindrome(&text)
+}
+
 /// Removes the trailing newline of the given string.
 ///
 /// It modifies the string in place.
@@ -58,7 +135,8 @@ pub fn chomp(text: &mut String) {
 
 /// Returns a centered string of length `width`.
 ///
-/// Padding is done with spaces.
+/// Padding is done with spaces. The width is the display width (see [`display_width`]),
+/// thus strings with East Asian wide characters are aligned correctly too.
 ///
 /// It's similar to Python's `str.center()`.
 ///
@@ -69,9 +147,304 @@ pub fn chomp(text: &mut String) {
 /// let result = jabba_lib::jstring::center(text, 3);
 ///
 /// assert_eq!(result, " * ");
+///
+/// assert_eq!(jabba_lib::jstring::center("日本", 6), " 日本 ");
 /// ```
 pub fn center(s: &str, width: usize) -> String {
-    format!("{s:^w$}", w = width)
+    let missing = width.saturating_sub(display_width(s));
+    let left = missing / 2;
+    format!("{}{}{}", " ".repeat(left), s, " ".repeat(missing - left))
+}
+
+/// Returns the display width of the string, i.e. the number of columns it takes up
+/// in a terminal (with a monospace font).
+///
+/// East Asian wide characters (e.g. "日") and most emoji take up two columns,
+/// combining marks and zero-width characters take up none.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::display_width("abc"), 3);
+/// assert_eq!(jabba_lib::jstring::display_width("日本"), 4);
+/// assert_eq!(jabba_lib::jstring::display_width("e\u{301}"), 1);
+/// ```
+///
+/// In Python, it would look like this (with the third-party `wcwidth` package):
+///
+/// ```python
+/// from wcwidth import wcswidth
+///
+/// assert wcswidth("abc") == 3
+/// assert wcswidth("日本") == 4
+/// assert wcswidth("e\u0301") == 1
+/// ```
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Options for [`wrap`] and [`fill`]. The defaults are the same as in Python's `textwrap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrapOptions {
+    /// The maximum width of the lines (display width, see [`display_width`]), including the indent.
+    pub width: usize,
+    /// Prepended to the first line.
+    pub initial_indent: String,
+    /// Prepended to the other lines.
+    pub subsequent_indent: String,
+    /// Expand the tabs to spaces (see [`expandtabs`]) before wrapping.
+    pub expand_tabs: bool,
+    /// Replace the whitespace characters (e.g. newlines) with spaces before wrapping.
+    pub replace_whitespace: bool,
+    /// Drop the whitespace at the beginning and at the end of the lines.
+    pub drop_whitespace: bool,
+    /// Break the words that are longer than `width`. Otherwise they are put on a line of their own.
+    pub break_long_words: bool,
+    /// Allow line breaks after the hyphens of compound words (e.g. "well-known").
+    pub break_on_hyphens: bool,
+    /// Truncate the text to this many lines (the last line ends with `placeholder`).
+    pub max_lines: Option<usize>,
+    /// Appended to the last line if the text was truncated.
+    pub placeholder: String,
+}
+
+impl Default for WrapOptions {
+    fn default() -> Self {
+        WrapOptions {
+            width: 70,
+            initial_indent: String::new(),
+            subsequent_indent: String::new(),
+            expand_tabs: true,
+            replace_whitespace: true,
+            drop_whitespace: true,
+            break_long_words: true,
+            break_on_hyphens: true,
+            max_lines: None,
+            placeholder: " [...]".to_string(),
+        }
+    }
+}
+
+impl WrapOptions {
+    /// The default options with the given width.
+    pub fn new(width: usize) -> Self {
+        WrapOptions {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+/// Wraps the text (a single paragraph) so that every line is at most `options.width` columns wide.
+/// Returns the lines, without the line breaks.
+///
+/// The width is the display width (see [`display_width`]), thus East Asian wide
+/// characters take up two columns.
+///
+/// It's like Python's `textwrap.wrap()`. Panics if the width is 0, or if the indent
+/// and the placeholder don't fit in the width (Python raises a `ValueError`).
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jstring::{self, WrapOptions};
+///
+/// let text = "The quick brown fox jumps over the lazy dog.";
+///
+/// assert_eq!(
+///     jstring::wrap(text, &WrapOptions::new(15)),
+///     ["The quick brown", "fox jumps over", "the lazy dog."]
+/// );
+///
+/// let options = WrapOptions {
+///     width: 20,
+///     initial_indent: "* ".to_string(),
+///     subsequent_indent: "  ".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     jstring::wrap(text, &options),
+///     ["* The quick brown", "  fox jumps over the", "  lazy dog."]
+/// );
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// import textwrap
+///
+/// text = "The quick brown fox jumps over the lazy dog."
+///
+/// assert textwrap.wrap(text, 15) == ["The quick brown", "fox jumps over", "the lazy dog."]
+/// assert textwrap.wrap(text, 20, initial_indent="* ", subsequent_indent="  ") == \
+///     ["* The quick brown", "  fox jumps over the", "  lazy dog."]
+/// ```
+pub fn wrap(text: &str, options: &WrapOptions) -> Vec<String> {
+    assert!(options.width > 0, "invalid width 0 (must be > 0)");
+    let text = if options.expand_tabs {
+        expandtabs(text, 8)
+    } else {
+        text.to_string()
+    };
+    let text = if options.replace_whitespace {
+        text.replace(is_wrap_whitespace, " ")
+    } else {
+        text
+    };
+    let mut chunks = split_chunks(&text, options.break_on_hyphens);
+    chunks.reverse();
+    wrap_chunks(chunks, options)
+}
+
+/// Wraps the text (a single paragraph), and returns a single string that contains the
+/// wrapped lines (see [`wrap`]).
+///
+/// It's like Python's `textwrap.fill()`.
+///
+/// # Examples
+///
+/// ```
+/// use jabba_lib::jstring::{self, WrapOptions};
+///
+/// let text = "The quick brown fox jumps over the lazy dog.";
+///
+/// assert_eq!(
+///     jstring::fill(text, &WrapOptions::new(15)),
+///     "The quick brown\nfox jumps over\nthe lazy dog."
+/// );
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// import textwrap
+///
+/// text = "The quick brown fox jumps over the lazy dog."
+///
+/// assert textwrap.fill(text, 15) == "The quick brown\nfox jumps over\nthe lazy dog."
+/// ```
+pub fn fill(text: &str, options: &WrapOptions) -> String {
+    wrap(text, options).join("\n")
+}
+
+/// Collapses the whitespace in the text, and truncates it to fit in `width` columns.
+/// The end of the text is replaced with `placeholder` (whole words are removed).
+///
+/// It's like Python's `textwrap.shorten()`. Panics if the placeholder doesn't fit in the width.
+///
+/// # Examples
+///
+/// ```
+/// let text = "Hello  world!";
+///
+/// assert_eq!(jabba_lib::jstring::shorten(text, 12, " [...]"), "Hello world!");
+/// assert_eq!(jabba_lib::jstring::shorten(text, 11, " [...]"), "Hello [...]");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// import textwrap
+///
+/// text = "Hello  world!"
+///
+/// assert textwrap.shorten(text, 12) == "Hello world!"
+/// assert textwrap.shorten(text, 11) == "Hello [...]"
+/// ```
+pub fn shorten(text: &str, width: usize, placeholder: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let options = WrapOptions {
+        width,
+        max_lines: Some(1),
+        placeholder: placeholder.to_string(),
+        ..Default::default()
+    };
+    fill(&text, &options)
+}
+
+/// Adds `prefix` to the beginning of the lines that are not empty (or whitespace only).
+///
+/// It's like Python's `textwrap.indent()`.
+///
+/// # Examples
+///
+/// ```
+/// let text = "hello\n\n  world\n";
+///
+/// assert_eq!(jabba_lib::jstring::indent(text, "> "), "> hello\n\n>   world\n");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// import textwrap
+///
+/// text = "hello\n\n  world\n"
+///
+/// assert textwrap.indent(text, "> ") == "> hello\n\n>   world\n"
+/// ```
+pub fn indent(text: &str, prefix: &str) -> String {
+    let mut result = String::new();
+    for line in splitlines(text, true) {
+        if !line.trim().is_empty() {
+            result.push_str(prefix);
+        }
+        result.push_str(line);
+    }
+    result
+}
+
+/// Removes the common leading whitespace from all the lines of the text.
+///
+/// Tabs and spaces are both treated as whitespace, but they are not equal
+/// (e.g. "  hello" and "\thello" have no common leading whitespace).
+/// The lines that consist solely of whitespace are normalized to empty lines.
+///
+/// It's like Python's `textwrap.dedent()`.
+///
+/// # Examples
+///
+/// ```
+/// let text = "    def f():\n        return 1\n";
+///
+/// assert_eq!(jabba_lib::jstring::dedent(text), "def f():\n    return 1\n");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// import textwrap
+///
+/// text = "    def f():\n        return 1\n"
+///
+/// assert textwrap.dedent(text) == "def f():\n    return 1\n"
+/// ```
+pub fn dedent(text: &str) -> String {
+    let is_blank = |c: char| c == ' ' || c == '\t';
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut margin: Option<&str> = None;
+    for line in &lines {
+        if line.trim_start_matches(is_blank).is_empty() {
+            continue;
+        }
+        let whitespace = &line[..line.len() - line.trim_start_matches(is_blank).len()];
+        margin = Some(match margin {
+            None => whitespace,
+            Some(margin) => common_prefix(margin, whitespace),
+        });
+    }
+    let margin = margin.unwrap_or("");
+    let lines: Vec<&str> = lines
+        .into_iter()
+        .map(|line| {
+            if line.trim_start_matches(is_blank).is_empty() {
+                ""
+            } else {
+                &line[margin.len()..]
+            }
+        })
+        .collect();
+    lines.join("\n")
 }
 
 /// Returns a capitalized version of the string.
@@ -100,74 +473,2093 @@ pub fn capitalize(s: &str) -> String {
     }
 }
 
-// ==========================================================================
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Returns the string left-justified in a string of length `width`.
+///
+/// Padding is done with `fillchar`. The width is the display width (see [`display_width`]).
+/// If `width` is not larger than the width of the string, the original string is returned.
+///
+/// It's like Python's `str.ljust()` (but Python counts the characters).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::ljust("Éva", 6, '*'), "Éva***");
+/// assert_eq!(jabba_lib::jstring::ljust("Éva", 2, '*'), "Éva");
+/// assert_eq!(jabba_lib::jstring::ljust("日本", 6, '*'), "日本**");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Éva".ljust(6, "*") == "Éva***"
+/// assert "Éva".ljust(2, "*") == "Éva"
+/// ```
+pub fn ljust(s: &str, width: usize, fillchar: char) -> String {
+    let mut result = s.to_string();
+    result.extend(padding(s, width, fillchar));
+    result
+}
 
-    #[test]
-    fn str_rev_test1() {
-        assert_eq!(str_rev(""), "");
-        assert_eq!(str_rev("a"), "a");
-        assert_eq!(str_rev("ab"), "ba");
-        assert_eq!(str_rev("anna"), "anna");
-        assert_eq!(str_rev("abc"), "cba");
-        assert_eq!(str_rev("AbCdE"), "EdCbA");
-    }
+/// Returns the string right-justified in a string of length `width`.
+///
+/// Padding is done with `fillchar`. The width is the display width (see [`display_width`]).
+/// If `width` is not larger than the width of the string, the original string is returned.
+///
+/// It's like Python's `str.rjust()` (but Python counts the characters).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::rjust("Éva", 6, '.'), "...Éva");
+/// assert_eq!(jabba_lib::jstring::rjust("日本", 6, '.'), "..日本");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Éva".rjust(6, ".") == "...Éva"
+/// ```
+pub fn rjust(s: &str, width: usize, fillchar: char) -> String {
+    let mut result: String = padding(s, width, fillchar).collect();
+    result.push_str(s);
+    result
+}
 
-    #[test]
-    fn is_palindrome_test1() {
-        assert!(is_palindrome(""));
-        assert!(is_palindrome("a"));
-        assert!(is_palindrome("aa"));
-        assert!(is_palindrome("anna"));
-        assert!(is_palindrome("görög"));
+/// Pads the string with zeros on the left, to fill a string of length `width`.
+///
+/// A leading sign (`+` or `-`) stays in front of the zeros.
+///
+/// It's like Python's `str.zfill()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::zfill("42", 5), "00042");
+/// assert_eq!(jabba_lib::jstring::zfill("-42", 5), "-0042");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "42".zfill(5) == "00042"
+/// assert "-42".zfill(5) == "-0042"
+/// ```
+pub fn zfill(s: &str, width: usize) -> String {
+    let zeros: String = padding(s, width, '0').collect();
+    match s.strip_prefix(['+', '-']) {
+        Some(rest) => format!("{}{}{}", &s[..1], zeros, rest),
+        None => format!("{}{}", zeros, s),
     }
+}
 
-    #[test]
-    fn is_palindrome_test2() {
-        assert_eq!(is_palindrome("ab"), false);
-        assert_eq!(is_palindrome("Anna"), false);
+/// Returns a titlecased version of the string: words start with an uppercase
+/// character and the remaining characters are lowercase.
+///
+/// A word is a sequence of cased characters (thus an apostrophe starts a new word).
+///
+/// It's like Python's `str.title()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::title("hello wORLD"), "Hello World");
+/// assert_eq!(jabba_lib::jstring::title("they're bill's"), "They'Re Bill'S");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "hello wORLD".title() == "Hello World"
+/// assert "they're bill's".title() == "They'Re Bill'S"
+/// ```
+pub fn title(s: &str) -> String {
+    let mut result = String::new();
+    let mut previous_is_cased = false;
+    for c in s.chars() {
+        if previous_is_cased {
+            result.extend(c.to_lowercase());
+        } else {
+            push_titlecase(&mut result, c);
+        }
+        previous_is_cased = is_cased(c);
     }
+    result
+}
 
-    #[test]
-    fn chomp_test1() {
-        let mut text = "".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "");
-        //
-        let mut text = "abc".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "abc");
-        //
-        let mut text = "\n".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "");
-        //
-        let mut text = "\r\n".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "");
-        //
-        let mut text = "\nend".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "\nend");
-        //
-        let mut text = "\r\nend".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "\r\nend");
-        //
-        let mut text = "longer\nstring\n".to_string();
-        chomp(&mut text);
-        assert_eq!(text, "longer\nstring");
+/// Converts the uppercase characters to lowercase and vice versa.
+///
+/// It's like Python's `str.swapcase()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::swapcase("Hello Éva"), "hELLO éVA");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Hello Éva".swapcase() == "hELLO éVA"
+/// ```
+pub fn swapcase(s: &str) -> String {
+    let mut result = String::new();
+    for c in s.chars() {
+        if c.is_uppercase() {
+            result.extend(c.to_lowercase());
+        } else if c.is_lowercase() {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
     }
+    result
+}
 
-    #[test]
-    fn center_test1() {
-        assert_eq!(center("-", 0), "-");
-        assert_eq!(center("-", 1), "-");
-        assert_eq!(center("-", 2), "- ");
-        assert_eq!(center("-", 3), " - ");
+/// Splits the string at the first occurrence of `sep`. Returns a 3-tuple
+/// containing the part before the separator, the separator itself, and the part after it.
+///
+/// If the separator is not found, returns the string itself followed by two empty strings.
+///
+/// It's like Python's `str.partition()`. Panics if `sep` is empty (Python raises a `ValueError`).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::partition("key=value=1", "="), ("key", "=", "value=1"));
+/// assert_eq!(jabba_lib::jstring::partition("key", "="), ("key", "", ""));
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "key=value=1".partition("=") == ("key", "=", "value=1")
+/// assert "key".partition("=") == ("key", "", "")
+/// ```
+pub fn partition<'a>(s: &'a str, sep: &str) -> (&'a str, &'a str, &'a str) {
+    assert!(!sep.is_empty(), "empty separator");
+    match s.find(sep) {
+        Some(i) => (&s[..i], &s[i..i + sep.len()], &s[i + sep.len()..]),
+        None => (s, "", ""),
+    }
+}
+
+/// Splits the string at the last occurrence of `sep`. Returns a 3-tuple
+/// containing the part before the separator, the separator itself, and the part after it.
+///
+/// If the separator is not found, returns two empty strings followed by the string itself.
+///
+/// It's like Python's `str.rpartition()`. Panics if `sep` is empty (Python raises a `ValueError`).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::rpartition("a/b/c.txt", "/"), ("a/b", "/", "c.txt"));
+/// assert_eq!(jabba_lib::jstring::rpartition("c.txt", "/"), ("", "", "c.txt"));
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "a/b/c.txt".rpartition("/") == ("a/b", "/", "c.txt")
+/// assert "c.txt".rpartition("/") == ("", "", "c.txt")
+/// ```
+pub fn rpartition<'a>(s: &'a str, sep: &str) -> (&'a str, &'a str, &'a str) {
+    assert!(!sep.is_empty(), "empty separator");
+    match s.rfind(sep) {
+        Some(i) => (&s[..i], &s[i..i + sep.len()], &s[i + sep.len()..]),
+        None => ("", "", s),
+    }
+}
+
+/// Replaces the tab characters with spaces, so that the text continues at the
+/// next tab stop. The tab stops are `tabsize` characters apart (the column is reset
+/// after a newline).
+///
+/// It's like Python's `str.expandtabs()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::expandtabs("01\t012\t0123\t", 4), "01  012 0123    ");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "01\t012\t0123\t".expandtabs(4) == "01  012 0123    "
+/// ```
+pub fn expandtabs(s: &str, tabsize: usize) -> String {
+    let mut result = String::new();
+    let mut column = 0;
+    for c in s.chars() {
+        match c {
+            '\t' => {
+                if tabsize > 0 {
+                    let spaces = tabsize - column % tabsize;
+                    result.extend(std::iter::repeat_n(' ', spaces));
+                    column += spaces;
+                }
+            }
+            '\n' | '\r' => {
+                result.push(c);
+                column = 0;
+            }
+            _ => {
+                result.push(c);
+                column += 1;
+            }
+        }
+    }
+    result
+}
+
+/// Splits the string at line boundaries. The line breaks are kept only if `keepends` is true.
+///
+/// The line boundaries are the same as in Python: `\n`, `\r`, `\r\n`, `\x0b`, `\x0c`,
+/// `\x1c`, `\x1d`, `\x1e`, `\u{85}`, `\u{2028}` and `\u{2029}`.
+///
+/// It's like Python's `str.splitlines()`.
+///
+/// # Examples
+///
+/// ```
+/// let text = "ab c\n\nde fg\rkl\r\n";
+///
+/// assert_eq!(jabba_lib::jstring::splitlines(text, false), ["ab c", "", "de fg", "kl"]);
+/// assert_eq!(
+///     jabba_lib::jstring::splitlines(text, true),
+///     ["ab c\n", "\n", "de fg\r", "kl\r\n"]
+/// );
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// text = "ab c\n\nde fg\rkl\r\n"
+///
+/// assert text.splitlines() == ["ab c", "", "de fg", "kl"]
+/// assert text.splitlines(keepends=True) == ["ab c\n", "\n", "de fg\r", "kl\r\n"]
+/// ```
+pub fn splitlines(s: &str, keepends: bool) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !is_line_boundary(c) {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        if c == '\r' && chars.peek().map(|&(_, next)| next) == Some('\n') {
+            chars.next();
+            end += 1;
+        }
+        lines.push(if keepends {
+            &s[start..end]
+        } else {
+            &s[start..i]
+        });
+        start = end;
+    }
+    if start < s.len() {
+        lines.push(&s[start..]);
+    }
+    lines
+}
+
+/// Removes the prefix from the string (if the string starts with it).
+///
+/// It's like Python's `str.removeprefix()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::removeprefix("TestHook", "Test"), "Hook");
+/// assert_eq!(jabba_lib::jstring::removeprefix("BaseTestCase", "Test"), "BaseTestCase");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "TestHook".removeprefix("Test") == "Hook"
+/// assert "BaseTestCase".removeprefix("Test") == "BaseTestCase"
+/// ```
+pub fn removeprefix<'a>(s: &'a str, prefix: &str) -> &'a str {
+    s.strip_prefix(prefix).unwrap_or(s)
+}
+
+/// Removes the suffix from the string (if the string ends with it).
+///
+/// It's like Python's `str.removesuffix()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::removesuffix("MiscTests", "Tests"), "Misc");
+/// assert_eq!(jabba_lib::jstring::removesuffix("TmpDirMixin", "Tests"), "TmpDirMixin");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "MiscTests".removesuffix("Tests") == "Misc"
+/// assert "TmpDirMixin".removesuffix("Tests") == "TmpDirMixin"
+/// ```
+pub fn removesuffix<'a>(s: &'a str, suffix: &str) -> &'a str {
+    s.strip_suffix(suffix).unwrap_or(s)
+}
+
+/// Returns a casefolded version of the string. Casefolded strings are used for
+/// caseless matching.
+///
+/// It's more aggressive than lowercasing, e.g. the German "ß" becomes "ss",
+/// and the Greek final sigma "ς" becomes "σ". Besides lowercasing, the special
+/// foldings of the Latin, Greek and Armenian letters and ligatures are done (but
+/// not the ones of the Cherokee letters and of the Greek letters with diacritics).
+///
+/// It's like Python's `str.casefold()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::casefold("Straße"), "strasse");
+/// assert_eq!(jabba_lib::jstring::casefold("Straße"), jabba_lib::jstring::casefold("STRASSE"));
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Straße".casefold() == "strasse"
+/// assert "Straße".casefold() == "STRASSE".casefold()
+/// ```
+pub fn casefold(s: &str) -> String {
+    let mut result = String::new();
+    for c in s.to_lowercase().chars() {
+        match CASEFOLD.iter().find(|(special, _)| *special == c) {
+            Some((_, folded)) => result.push_str(folded),
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+/// Returns `true` if the string is a valid identifier (in Python), i.e. it starts
+/// with a letter or an underscore, followed by letters, digits or underscores
+/// (more precisely: Unicode `XID_Start` and `XID_Continue` characters).
+///
+/// It's like Python's `str.isidentifier()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isidentifier("_név2"), true);
+/// assert_eq!(jabba_lib::jstring::isidentifier("2nd"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "_név2".isidentifier() == True
+/// assert "2nd".isidentifier() == False
+/// ```
+pub fn isidentifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) if first == '_' || unicode_ident::is_xid_start(first) => {
+            chars.all(unicode_ident::is_xid_continue)
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if all characters in the string are alphabetic (letters),
+/// and there is at least one character.
+///
+/// It's like Python's `str.isalpha()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isalpha("Éva"), true);
+/// assert_eq!(jabba_lib::jstring::isalpha("Éva2"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Éva".isalpha() == True
+/// assert "Éva2".isalpha() == False
+/// ```
+pub fn isalpha(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_alpha_char)
+}
+
+/// Returns `true` if all characters in the string are decimal characters
+/// (that can form numbers in base 10), and there is at least one character.
+///
+/// Decimal characters are the digits of the various scripts (Unicode category `Nd`),
+/// e.g. "0", "٣" (Arabic-Indic three).
+///
+/// It's like Python's `str.isdecimal()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isdecimal("2024"), true);
+/// assert_eq!(jabba_lib::jstring::isdecimal("²"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "2024".isdecimal() == True
+/// assert "²".isdecimal() == False
+/// ```
+pub fn isdecimal(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_decimal_char)
+}
+
+/// Returns `true` if all characters in the string are digits, and there is
+/// at least one character.
+///
+/// Digits are the decimal characters (see [`isdecimal`]) and the characters
+/// that stand for a single digit, like superscripts ("²") and circled digits ("①").
+///
+/// It's like Python's `str.isdigit()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isdigit("2²①"), true);
+/// assert_eq!(jabba_lib::jstring::isdigit("½"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "2²①".isdigit() == True
+/// assert "½".isdigit() == False
+/// ```
+pub fn isdigit(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_digit_char)
+}
+
+/// Returns `true` if all characters in the string are numeric, and there is
+/// at least one character.
+///
+/// Numeric characters are the digits (see [`isdigit`]), fractions ("½"),
+/// Roman numerals ("Ⅻ"), the CJK numerals ("五"), etc.
+///
+/// It's like Python's `str.isnumeric()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isnumeric("2²½Ⅻ五"), true);
+/// assert_eq!(jabba_lib::jstring::isnumeric("-1"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "2²½Ⅻ五".isnumeric() == True
+/// assert "-1".isnumeric() == False
+/// ```
+pub fn isnumeric(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_numeric_char)
+}
+
+/// Returns `true` if all characters in the string are alphanumeric (alphabetic
+/// or numeric), and there is at least one character.
+///
+/// It's like Python's `str.isalnum()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isalnum("Éva2"), true);
+/// assert_eq!(jabba_lib::jstring::isalnum("Éva 2"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Éva2".isalnum() == True
+/// assert "Éva 2".isalnum() == False
+/// ```
+pub fn isalnum(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| is_alpha_char(c) || is_numeric_char(c))
+}
+
+/// Returns `true` if all characters in the string are whitespace, and there is
+/// at least one character.
+///
+/// It's like Python's `str.isspace()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isspace(" \t\n\u{a0}"), true);
+/// assert_eq!(jabba_lib::jstring::isspace(""), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert " \t\n\xa0".isspace() == True
+/// assert "".isspace() == False
+/// ```
+pub fn isspace(s: &str) -> bool {
+    // Python counts the information separators (\x1c - \x1f) as whitespace too
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_whitespace() || ('\x1c'..='\x1f').contains(&c))
+}
+
+/// Returns `true` if all cased characters in the string are lowercase, and
+/// there is at least one cased character.
+///
+/// It's like Python's `str.islower()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::islower("éva 2"), true);
+/// assert_eq!(jabba_lib::jstring::islower("Éva"), false);
+/// assert_eq!(jabba_lib::jstring::islower("2"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "éva 2".islower() == True
+/// assert "Éva".islower() == False
+/// assert "2".islower() == False
+/// ```
+pub fn islower(s: &str) -> bool {
+    let mut cased = false;
+    for c in s.chars() {
+        if c.is_uppercase() || is_titlecase(c) {
+            return false;
+        }
+        cased = cased || c.is_lowercase();
+    }
+    cased
+}
+
+/// Returns `true` if all cased characters in the string are uppercase, and
+/// there is at least one cased character.
+///
+/// It's like Python's `str.isupper()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isupper("ÉVA 2"), true);
+/// assert_eq!(jabba_lib::jstring::isupper("Éva"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "ÉVA 2".isupper() == True
+/// assert "Éva".isupper() == False
+/// ```
+pub fn isupper(s: &str) -> bool {
+    let mut cased = false;
+    for c in s.chars() {
+        if c.is_lowercase() || is_titlecase(c) {
+            return false;
+        }
+        cased = cased || c.is_uppercase();
+    }
+    cased
+}
+
+/// Returns `true` if the string is titlecased (see [`title`]), and there is
+/// at least one cased character.
+///
+/// It's like Python's `str.istitle()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::istitle("Hello Éva"), true);
+/// assert_eq!(jabba_lib::jstring::istitle("Hello éva"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Hello Éva".istitle() == True
+/// assert "Hello éva".istitle() == False
+/// ```
+pub fn istitle(s: &str) -> bool {
+    let mut cased = false;
+    let mut previous_is_cased = false;
+    for c in s.chars() {
+        if c.is_uppercase() || is_titlecase(c) {
+            if previous_is_cased {
+                return false;
+            }
+            previous_is_cased = true;
+            cased = true;
+        } else if c.is_lowercase() {
+            if !previous_is_cased {
+                return false;
+            }
+            previous_is_cased = true;
+            cased = true;
+        } else {
+            previous_is_cased = false;
+        }
+    }
+    cased
+}
+
+/// Returns `true` if the string is empty or all characters in the string are ASCII.
+///
+/// It's like Python's `str.isascii()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isascii("Eva"), true);
+/// assert_eq!(jabba_lib::jstring::isascii("Éva"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Eva".isascii() == True
+/// assert "Éva".isascii() == False
+/// ```
+pub fn isascii(s: &str) -> bool {
+    s.is_ascii()
+}
+
+/// Returns `true` if the string is empty or all characters in the string are printable.
+///
+/// The non-printable characters are the control and format characters, the
+/// unassigned code points, and the separators except the space.
+///
+/// It's like Python's `str.isprintable()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::isprintable("Hello Éva!"), true);
+/// assert_eq!(jabba_lib::jstring::isprintable("Hello\n"), false);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert "Hello Éva!".isprintable() == True
+/// assert "Hello\n".isprintable() == False
+/// ```
+pub fn isprintable(s: &str) -> bool {
+    s.chars().all(is_printable_char)
+}
+
+/// Returns the number of occurrences of `sub` in the string.
+///
+/// If `overlapping` is false, the non-overlapping occurrences are counted (like in Python).
+/// If `sub` is empty, the number of positions between the characters is returned
+/// (i.e. the number of characters + 1).
+///
+/// It's like Python's `str.count()`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jstring::count("aaaa", "aa", false), 2);
+/// assert_eq!(jabba_lib::jstring::count("aaaa", "aa", true), 3);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// import re
+///
+/// assert "aaaa".count("aa") == 2
+/// assert len(re.findall("(?=aa)", "aaaa")) == 3  # overlapping
+/// ```
+pub fn count(s: &str, sub: &str, overlapping: bool) -> usize {
+    if sub.is_empty() {
+        return s.chars().count() + 1;
+    }
+    // else
+    if !overlapping {
+        return s.matches(sub).count();
+    }
+    // else
+    let mut result = 0;
+    let mut rest = s;
+    while let Some(i) = rest.find(sub) {
+        result += 1;
+        // step over the first character of the match only
+        let step = rest[i..].chars().next().unwrap().len_utf8();
+        rest = &rest[i + step..];
+    }
+    result
+}
+
+/// Returns the slice `s[start:stop:step]` of the string. The indices refer to characters
+/// (not to bytes).
+///
+/// Negative indices count from the end, and a negative step goes backwards.
+/// Out-of-range indices are clamped (no panic). `None` means that the index was omitted.
+/// See also [`slice_graphemes`].
+///
+/// It's like Python's slicing. Panics if `step` is 0 (Python raises a `ValueError`).
+///
+/// # Examples
+///
+/// ```
+/// let text = "Árvíztűrő";
+///
+/// assert_eq!(jabba_lib::jstring::slice(text, Some(-4), None, 1), "tűrő");
+/// assert_eq!(jabba_lib::jstring::slice(text, None, Some(5), 2), "Ávz");
+/// assert_eq!(jabba_lib::jstring::slice(text, None, None, -1), "őrűtzívrÁ");
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// text = "Árvíztűrő"
+///
+/// assert text[-4:] == "tűrő"
+/// assert text[:5:2] == "Ávz"
+/// assert text[::-1] == "őrűtzívrÁ"
+/// ```
+pub fn slice(s: &str, start: Option<isize>, stop: Option<isize>, step: isize) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    jvec::slice(&chars, start, stop, step).into_iter().collect()
+}
+
+/// Returns the slice `s[start:stop:step]` of the string. The indices refer to
+/// extended grapheme clusters, i.e. to user-perceived characters.
+///
+/// Thus, a letter with combining accents or an emoji sequence is never cut in two.
+/// Otherwise it works like [`slice`].
+///
+/// # Examples
+///
+/// ```
+/// let text = "e\u{301}a👍🏽";  // 'e' + combining acute accent, 'a', thumbs up + skin tone
+///
+/// assert_eq!(jabba_lib::jstring::slice_graphemes(text, None, Some(1), 1), "e\u{301}");
+/// assert_eq!(jabba_lib::jstring::slice_graphemes(text, None, None, -1), "👍🏽ae\u{301}");
+/// assert_eq!(jabba_lib::jstring::slice(text, None, Some(1), 1), "e");
+/// ```
+///
+/// Python has no built-in support for it; with the third-party `grapheme` package:
+///
+/// ```python
+/// import grapheme
+///
+/// text = "e\u0301a👍🏽"
+///
+/// assert grapheme.slice(text, 0, 1) == "e\u0301"
+/// assert "".join(list(grapheme.graphemes(text))[::-1]) == "👍🏽ae\u0301"
+/// ```
+pub fn slice_graphemes(s: &str, start: Option<isize>, stop: Option<isize>, step: isize) -> String {
+    let graphemes: Vec<&str> = s.graphemes(true).collect();
+    jvec::slice(&graphemes, start, stop, step).concat()
+}
+
+/// A value that can be substituted into a template by [`format`].
+///
+/// The `From` implementations make it easy to build the arguments,
+/// e.g. `Value::from(42)` or `"text".into()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Value {
+    /// The name of the corresponding Python type (used in the error messages).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "str",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+        }
+    }
+}
+
+/// Formats the value like Python's `str()`, e.g. floats always have a decimal point.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{}", float_repr(*x)),
+            Value::Bool(true) => write!(f, "True"),
+            Value::Bool(false) => write!(f, "False"),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<char> for Value {
+    fn from(c: char) -> Self {
+        Value::Str(c.to_string())
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<f32> for Value {
+    fn from(x: f32) -> Self {
+        Value::Float(x.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Float(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+/// The error returned by [`format`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// A single `{` or `}` at the given byte position (write `{{` and `}}` for literal braces).
+    UnmatchedBrace(usize),
+    /// Automatic (`{}`) and manual (`{0}`) field numbering are mixed.
+    MixedNumbering,
+    /// There's no positional argument with the given index.
+    MissingPositional(usize),
+    /// There's no named argument with the given name.
+    MissingNamed(String),
+    /// The field name is not supported (e.g. attribute or index access, like `{a.b}` or `{a[0]}`).
+    InvalidFieldName(String),
+    /// The conversion is not `!s`, `!r` or `!a`.
+    InvalidConversion(String),
+    /// The format spec cannot be parsed, or its parts don't fit together.
+    InvalidSpec { spec: String, reason: &'static str },
+    /// The presentation type is not valid for the type of the value (e.g. `{:d}` for a string).
+    UnknownFormatCode { code: char, type_name: &'static str },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::UnmatchedBrace(pos) => write!(f, "single brace at position {}", pos),
+            FormatError::MixedNumbering => write!(
+                f,
+                "cannot switch between automatic and manual field numbering"
+            ),
+            FormatError::MissingPositional(index) => {
+                write!(f, "missing positional argument: {}", index)
+            }
+            FormatError::MissingNamed(name) => write!(f, "missing named argument: {:?}", name),
+            FormatError::InvalidFieldName(name) => write!(f, "invalid field name: {:?}", name),
+            FormatError::InvalidConversion(conversion) => {
+                write!(f, "invalid conversion: {:?}", conversion)
+            }
+            FormatError::InvalidSpec { spec, reason } => {
+                write!(f, "invalid format spec {:?}: {}", spec, reason)
+            }
+            FormatError::UnknownFormatCode { code, type_name } => write!(
+                f,
+                "unknown format code {:?} for a value of type {}",
+                code, type_name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Formats a template at runtime, using Python's format string syntax.
+///
+/// The replacement fields can refer to the positional arguments automatically (`{}`),
+/// by index (`{0}`) or to the named arguments by name (`{name}`). A field can have
+/// a conversion (`!s`, `!r`, `!a`) and a format spec (`[[fill]align][sign][#][0][width][,|_][.precision][type]`),
+/// and the format spec can contain nested fields (`{:>{width}}`).
+/// Literal braces are written as `{{` and `}}`.
+///
+/// Differences from Python: the width is the display width (see [`display_width`]),
+/// attribute and index access in the field names (`{a.b}`, `{a[0]}`) are not supported,
+/// and the locale-aware `n` type works like `d` / `g`.
+///
+/// It's like Python's `str.format()`.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use jabba_lib::jstring::{self, FormatError, Value};
+///
+/// let args = [Value::from(3.14159), Value::from(1234567)];
+/// let kwargs = HashMap::from([("name", Value::from("Éva")), ("width", Value::from(6))]);
+///
+/// let text = jstring::format("{name:>{width}}: {0:.2f}, {1:,}, {name!r}", &args, &kwargs);
+/// assert_eq!(text.unwrap(), "   Éva: 3.14, 1,234,567, 'Éva'");
+///
+/// let text = jstring::format("{age}", &args, &kwargs);
+/// assert_eq!(text, Err(FormatError::MissingNamed("age".to_string())));
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// args = [3.14159, 1234567]
+/// kwargs = {"name": "Éva", "width": 6}
+///
+/// text = "{name:>{width}}: {0:.2f}, {1:,}, {name!r}".format(*args, **kwargs)
+/// assert text == "   Éva: 3.14, 1,234,567, 'Éva'"
+/// ```
+pub fn format(
+    template: &str,
+    args: &[Value],
+    kwargs: &HashMap<&str, Value>,
+) -> Result<String, FormatError> {
+    let mut formatter = Formatter {
+        args,
+        kwargs,
+        next_index: Some(0),
+        manual: false,
+    };
+    formatter.render(template, 0)
+}
+
+/*********
+  private
+**********/
+
+/// The fill characters needed to make the string `width` columns wide.
+fn padding(s: &str, width: usize, fillchar: char) -> impl Iterator<Item = char> {
+    let missing = width.saturating_sub(display_width(s));
+    // a wide fill character takes up two columns
+    let fill_width = UnicodeWidthChar::width(fillchar).unwrap_or(1).max(1);
+    std::iter::repeat_n(fillchar, missing / fill_width)
+}
+
+/// Like `to_uppercase()`, but the Latin digraphs (e.g. "ǆ") have a separate titlecase form ("ǅ").
+fn push_titlecase(result: &mut String, c: char) {
+    match c {
+        'Ǆ' | 'ǅ' | 'ǆ' => result.push('ǅ'),
+        'Ǉ' | 'ǈ' | 'ǉ' => result.push('ǈ'),
+        'Ǌ' | 'ǋ' | 'ǌ' => result.push('ǋ'),
+        'Ǳ' | 'ǲ' | 'ǳ' => result.push('ǲ'),
+        _ => result.extend(c.to_uppercase()),
+    }
+}
+
+fn is_titlecase(c: char) -> bool {
+    get_general_category(c) == GeneralCategory::TitlecaseLetter
+}
+
+fn is_cased(c: char) -> bool {
+    c.is_lowercase() || c.is_uppercase() || is_titlecase(c)
+}
+
+fn is_alpha_char(c: char) -> bool {
+    matches!(
+        get_general_category(c),
+        GeneralCategory::UppercaseLetter
+            | GeneralCategory::LowercaseLetter
+            | GeneralCategory::TitlecaseLetter
+            | GeneralCategory::ModifierLetter
+            | GeneralCategory::OtherLetter
+    )
+}
+
+fn is_decimal_char(c: char) -> bool {
+    get_general_category(c) == GeneralCategory::DecimalNumber
+}
+
+fn is_digit_char(c: char) -> bool {
+    is_decimal_char(c) || DIGITS.iter().any(|(lo, hi)| (*lo..=*hi).contains(&c))
+}
+
+fn is_numeric_char(c: char) -> bool {
+    c.is_numeric() || CJK_NUMERALS.binary_search(&c).is_ok()
+}
+
+fn is_printable_char(c: char) -> bool {
+    c == ' '
+        || !matches!(
+            get_general_category(c),
+            GeneralCategory::Control
+                | GeneralCategory::Format
+                | GeneralCategory::Surrogate
+                | GeneralCategory::PrivateUse
+                | GeneralCategory::Unassigned
+                | GeneralCategory::LineSeparator
+                | GeneralCategory::ParagraphSeparator
+                | GeneralCategory::SpaceSeparator
+        )
+}
+
+fn is_line_boundary(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r'
+            | '\x0b'
+            | '\x0c'
+            | '\x1c'
+            | '\x1d'
+            | '\x1e'
+            | '\u{85}'
+            | '\u{2028}'
+            | '\u{2029}'
+    )
+}
+
+/// The state of a [`format`] call (shared by the nested fields).
+struct Formatter<'a> {
+    args: &'a [Value],
+    kwargs: &'a HashMap<&'a str, Value>,
+    /// The index of the next automatically numbered field.
+    next_index: Option<usize>,
+    /// Whether a manually numbered field was used.
+    manual: bool,
+}
+
+impl Formatter<'_> {
+    fn render(&mut self, template: &str, depth: usize) -> Result<String, FormatError> {
+        let mut result = String::new();
+        let mut chars = template.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, next)| next) == Some('{') => {
+                    chars.next();
+                    result.push('{');
+                }
+                '{' => {
+                    let mut level = 1;
+                    let mut end = None;
+                    for (j, d) in chars.by_ref() {
+                        match d {
+                            '{' => level += 1,
+                            '}' => level -= 1,
+                            _ => {}
+                        }
+                        if level == 0 {
+                            end = Some(j);
+                            break;
+                        }
+                    }
+                    let end = end.ok_or(FormatError::UnmatchedBrace(i))?;
+                    let field = &template[i + 1..end];
+                    result.push_str(&self.format_field(field, depth)?);
+                }
+                '}' if chars.peek().map(|&(_, next)| next) == Some('}') => {
+                    chars.next();
+                    result.push('}');
+                }
+                '}' => return Err(FormatError::UnmatchedBrace(i)),
+                _ => result.push(c),
+            }
+        }
+        Ok(result)
+    }
+
+    fn format_field(&mut self, field: &str, depth: usize) -> Result<String, FormatError> {
+        let name_end = field.find(['!', ':']).unwrap_or(field.len());
+        let (name, mut rest) = field.split_at(name_end);
+        let mut value = self.lookup(name)?;
+
+        if let Some(after) = rest.strip_prefix('!') {
+            let conversion_end = after.find(':').unwrap_or(after.len());
+            let (conversion, after) = after.split_at(conversion_end);
+            value = match conversion {
+                "s" => Value::Str(value.to_string()),
+                "r" => Value::Str(repr(&value, false)),
+                "a" => Value::Str(repr(&value, true)),
+                _ => return Err(FormatError::InvalidConversion(conversion.to_string())),
+            };
+            rest = after;
+        }
+        let spec = rest.strip_prefix(':').unwrap_or("");
+        let spec = if spec.contains(['{', '}']) {
+            if depth >= 1 {
+                return Err(FormatError::InvalidSpec {
+                    spec: spec.to_string(),
+                    reason: "too deeply nested fields",
+                });
+            }
+            self.render(spec, depth + 1)?
+        } else {
+            spec.to_string()
+        };
+        format_value(&value, &spec)
+    }
+
+    fn lookup(&mut self, name: &str) -> Result<Value, FormatError> {
+        if name.is_empty() {
+            if self.manual {
+                return Err(FormatError::MixedNumbering);
+            }
+            let index = self.next_index.unwrap_or(0);
+            self.next_index = Some(index + 1);
+            return self
+                .args
+                .get(index)
+                .cloned()
+                .ok_or(FormatError::MissingPositional(index));
+        }
+        if name.contains(['.', '[', ']', '{', '}']) {
+            return Err(FormatError::InvalidFieldName(name.to_string()));
+        }
+        if name.bytes().all(|b| b.is_ascii_digit()) {
+            if self.next_index != Some(0) {
+                return Err(FormatError::MixedNumbering);
+            }
+            self.manual = true;
+            let index: usize = name
+                .parse()
+                .map_err(|_| FormatError::InvalidFieldName(name.to_string()))?;
+            return self
+                .args
+                .get(index)
+                .cloned()
+                .ok_or(FormatError::MissingPositional(index));
+        }
+        self.kwargs
+            .get(name)
+            .cloned()
+            .ok_or_else(|| FormatError::MissingNamed(name.to_string()))
+    }
+}
+
+/// A parsed format spec: `[[fill]align][sign][#][0][width][grouping][.precision][type]`.
+#[derive(Debug, Default)]
+struct Spec {
+    fill: Option<char>,
+    align: Option<char>,
+    sign: Option<char>,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    grouping: Option<char>,
+    precision: Option<usize>,
+    kind: Option<char>,
+}
+
+impl Spec {
+    fn parse(spec: &str) -> Result<Spec, FormatError> {
+        let invalid = |reason| FormatError::InvalidSpec {
+            spec: spec.to_string(),
+            reason,
+        };
+        let chars: Vec<char> = spec.chars().collect();
+        let mut result = Spec::default();
+        let mut i = 0;
+        let is_align = |c: Option<&char>| matches!(c, Some('<' | '>' | '^' | '='));
+
+        if is_align(chars.get(1)) {
+            result.fill = Some(chars[0]);
+            result.align = Some(chars[1]);
+            i = 2;
+        } else if is_align(chars.first()) {
+            result.align = Some(chars[0]);
+            i = 1;
+        }
+        if let Some(&c @ ('+' | '-' | ' ')) = chars.get(i) {
+            result.sign = Some(c);
+            i += 1;
+        }
+        if chars.get(i) == Some(&'#') {
+            result.alternate = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            result.zero = true;
+            i += 1;
+        }
+        let digits = |i: &mut usize| {
+            let start = *i;
+            while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                *i += 1;
+            }
+            let digits: String = chars[start..*i].iter().collect();
+            (!digits.is_empty()).then(|| digits.parse::<usize>())
+        };
+        if let Some(width) = digits(&mut i) {
+            result.width = width.map_err(|_| invalid("too large width"))?;
+        }
+        if let Some(&c @ (',' | '_')) = chars.get(i) {
+            result.grouping = Some(c);
+            i += 1;
+        }
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            match digits(&mut i) {
+                Some(precision) => {
+                    result.precision = Some(precision.map_err(|_| invalid("too large precision"))?)
+                }
+                None => return Err(invalid("missing precision")),
+            }
+        }
+        match chars.len() - i {
+            0 => {}
+            1 => result.kind = Some(chars[i]),
+            _ => return Err(invalid("unexpected characters")),
+        }
+        if result.zero {
+            // the '0' flag means zero-padding (after the sign for numbers, see format_value)
+            result.fill = result.fill.or(Some('0'));
+        }
+        Ok(result)
+    }
+}
+
+fn format_value(value: &Value, spec_text: &str) -> Result<String, FormatError> {
+    let mut spec = Spec::parse(spec_text)?;
+    if spec.zero && !matches!(value, Value::Str(_)) {
+        // zero-padding goes after the sign unless the alignment is set explicitly;
+        // strings keep their default left alignment (like in Python 3.10+)
+        spec.align = spec.align.or(Some('='));
+    }
+    let invalid = |reason| FormatError::InvalidSpec {
+        spec: spec_text.to_string(),
+        reason,
+    };
+    let unknown = |code| FormatError::UnknownFormatCode {
+        code,
+        type_name: value.type_name(),
+    };
+    // like in Python, a bool is formatted as an int if there's a format spec
+    if let (Value::Bool(_), "") = (value, spec_text) {
+        return Ok(value.to_string());
+    }
+    match (value, spec.kind) {
+        (Value::Str(_), None | Some('s')) => {
+            if spec.sign.is_some() {
+                return Err(invalid("sign not allowed with strings"));
+            }
+            if spec.alternate {
+                return Err(invalid("alternate form (#) not allowed with strings"));
+            }
+            if spec.align == Some('=') {
+                return Err(invalid("'=' alignment not allowed with strings"));
+            }
+            if spec.grouping.is_some() {
+                return Err(invalid("grouping not allowed with strings"));
+            }
+            let text = value.to_string();
+            let text = match spec.precision {
+                Some(precision) => text.chars().take(precision).collect(),
+                None => text,
+            };
+            Ok(pad(&spec, "", &text, '<'))
+        }
+        (Value::Int(_) | Value::Bool(_), None | Some('d' | 'n' | 'b' | 'o' | 'x' | 'X' | 'c')) => {
+            let n = match value {
+                Value::Int(n) => *n,
+                _ => i64::from(*value == Value::Bool(true)),
+            };
+            if spec.precision.is_some() {
+                return Err(invalid("precision not allowed with integers"));
+            }
+            let kind = spec.kind.unwrap_or('d');
+            if kind == 'c' {
+                if spec.sign.is_some() || spec.alternate || spec.grouping.is_some() {
+                    return Err(invalid("sign, # and grouping not allowed with 'c'"));
+                }
+                let c = u32::try_from(n)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(invalid("the value is not a valid character"))?;
+                return Ok(pad(&spec, "", &c.to_string(), '<'));
+            }
+            let (digits, radix_prefix, group_size) = match kind {
+                'b' => (format!("{:b}", n.unsigned_abs()), "0b", 4),
+                'o' => (format!("{:o}", n.unsigned_abs()), "0o", 4),
+                'x' => (format!("{:x}", n.unsigned_abs()), "0x", 4),
+                'X' => (format!("{:X}", n.unsigned_abs()), "0X", 4),
+                _ => (n.unsigned_abs().to_string(), "", 3),
+            };
+            if spec.grouping == Some(',') && group_size == 4 {
+                return Err(invalid(
+                    "',' grouping not allowed with 'b', 'o', 'x' and 'X'",
+                ));
+            }
+            let mut prefix = sign_of(&spec, n < 0).to_string();
+            if spec.alternate {
+                prefix.push_str(radix_prefix);
+            }
+            Ok(pad_number(&spec, &prefix, &digits, "", group_size))
+        }
+        (
+            Value::Int(_) | Value::Bool(_) | Value::Float(_),
+            Some('e' | 'E' | 'f' | 'F' | 'g' | 'G' | '%'),
+        )
+        | (Value::Float(_), None | Some('n')) => {
+            let x = match value {
+                Value::Int(n) => *n as f64,
+                Value::Bool(b) => f64::from(u8::from(*b)),
+                Value::Float(x) => *x,
+                Value::Str(_) => unreachable!(),
+            };
+            let kind = spec.kind.map(|k| if k == 'n' { 'g' } else { k });
+            let negative = x.is_sign_negative() && !x.is_nan();
+            let prefix = sign_of(&spec, negative);
+            let body = if x.is_finite() {
+                format_float(x.abs(), kind, spec.precision, spec.alternate)
+            } else {
+                let name = if x.is_nan() { "nan" } else { "inf" };
+                let percent = if kind == Some('%') { "%" } else { "" };
+                format!("{}{}", name, percent)
+            };
+            let body = if matches!(kind, Some('E' | 'F' | 'G')) {
+                body.to_uppercase()
+            } else {
+                body
+            };
+            // the grouping applies to the integer part only
+            let split = body
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(body.len());
+            let (digits, rest) = body.split_at(split);
+            if digits.is_empty() {
+                return Ok(pad(&spec, prefix, &body, '>'));
+            }
+            Ok(pad_number(&spec, prefix, digits, rest, 3))
+        }
+        (_, Some(code)) => Err(unknown(code)),
+    }
+}
+
+fn sign_of(spec: &Spec, negative: bool) -> &'static str {
+    match (negative, spec.sign) {
+        (true, _) => "-",
+        (false, Some('+')) => "+",
+        (false, Some(' ')) => " ",
+        _ => "",
+    }
+}
+
+/// Pads a number. With zero-padding, the zeros are grouped too (like in Python).
+fn pad_number(spec: &Spec, prefix: &str, digits: &str, rest: &str, group_size: usize) -> String {
+    let mut digits = digits.to_string();
+    let grouped = |digits: &str| match spec.grouping {
+        Some(separator) => group(digits, separator, group_size),
+        None => digits.to_string(),
+    };
+    if spec.zero && spec.align == Some('=') && spec.fill == Some('0') {
+        while display_width(prefix) + grouped(&digits).len() + display_width(rest) < spec.width {
+            digits.insert(0, '0');
+        }
+    }
+    pad(spec, prefix, &format!("{}{}", grouped(&digits), rest), '>')
+}
+
+/// Inserts the separator between the groups of the digits (counted from the right).
+fn group(digits: &str, separator: char, group_size: usize) -> String {
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(group_size) {
+            result.push(separator);
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Pads `prefix` + `body` to the width of the spec. The `=` alignment
+/// puts the padding between the prefix (sign) and the body.
+fn pad(spec: &Spec, prefix: &str, body: &str, default_align: char) -> String {
+    let fill = spec.fill.unwrap_or(' ');
+    let missing = spec
+        .width
+        .saturating_sub(display_width(prefix) + display_width(body));
+    let fill_with = |n: usize| fill.to_string().repeat(n);
+    match spec.align.unwrap_or(default_align) {
+        '<' => format!("{}{}{}", prefix, body, fill_with(missing)),
+        '^' => format!(
+            "{}{}{}{}",
+            fill_with(missing / 2),
+            prefix,
+            body,
+            fill_with(missing - missing / 2)
+        ),
+        '=' => format!("{}{}{}", prefix, fill_with(missing), body),
+        _ => format!("{}{}{}", fill_with(missing), prefix, body),
+    }
+}
+
+/// Formats a finite, non-negative float. `kind` is `e`, `f`, `g`, `%` (lowercase) or
+/// `None` (Python's `str()`, or like `g` if the precision is given).
+fn format_float(x: f64, kind: Option<char>, precision: Option<usize>, alternate: bool) -> String {
+    let point = |mut s: String| {
+        if alternate && !s.contains('.') {
+            s.push('.');
+        }
+        s
+    };
+    match kind.map(|k| k.to_ascii_lowercase()) {
+        Some('f') => point(format!("{:.*}", precision.unwrap_or(6), x)),
+        Some('%') => point(format!("{:.*}", precision.unwrap_or(6), x * 100.0)) + "%",
+        Some('e') => {
+            let (mantissa, exponent) = scientific(x, precision.unwrap_or(6));
+            format!("{}e{}", point(mantissa), exponent)
+        }
+        Some(_) => general(x, precision.unwrap_or(6), alternate, false),
+        None => match precision {
+            Some(precision) => general(x, precision, alternate, true),
+            None => float_repr(x),
+        },
+    }
+}
+
+/// Python's `g` format. With `repr_like`, the fixed-point results keep at least
+/// one digit after the decimal point (Python's format without a type).
+fn general(x: f64, precision: usize, alternate: bool, repr_like: bool) -> String {
+    let precision = precision.max(1);
+    let (_, exponent) = scientific(x, precision - 1);
+    let exp: i32 = exponent.parse().unwrap();
+    let strip = |s: String| {
+        if alternate || !s.contains('.') {
+            s
+        } else {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    };
+    // Python's format without a type switches to the scientific notation one digit earlier
+    let limit = if repr_like { precision - 1 } else { precision };
+    if -4 <= exp && exp < limit as i32 {
+        let decimals = (precision as i32 - 1 - exp) as usize;
+        let mut s = strip(format!("{:.*}", decimals, x));
+        if alternate && !s.contains('.') {
+            s.push('.');
+        }
+        if repr_like && !s.contains('.') {
+            s.push_str(".0");
+        }
+        s
+    } else {
+        let (mantissa, exponent) = scientific(x, precision - 1);
+        let mut mantissa = strip(mantissa);
+        if alternate && !mantissa.contains('.') {
+            mantissa.push('.');
+        }
+        format!("{}e{}", mantissa, exponent)
+    }
+}
+
+/// Formats a float with `precision` decimals in scientific notation, and returns
+/// the mantissa and the exponent (in Python's style, e.g. "+05").
+fn scientific(x: f64, precision: usize) -> (String, String) {
+    split_exponent(&format!("{:.*e}", precision, x))
+}
+
+fn split_exponent(s: &str) -> (String, String) {
+    let (mantissa, exponent) = s.split_once('e').unwrap();
+    let exp: i32 = exponent.parse().unwrap();
+    let sign = if exp < 0 { '-' } else { '+' };
+    (mantissa.to_string(), format!("{}{:02}", sign, exp.abs()))
+}
+
+/// Python's `repr()` of a float: the shortest form that reads back to the same value.
+fn float_repr(x: f64) -> String {
+    if x.is_nan() {
+        return "nan".to_string();
+    }
+    if x.is_infinite() {
+        return if x < 0.0 { "-inf" } else { "inf" }.to_string();
+    }
+    // else
+    // the fewest correctly rounded digits that read back to the same value (like Python);
+    // Rust's shortest form can round a tie the other way
+    let (mantissa, exponent) = (0..17)
+        .map(|precision| format!("{:.*e}", precision, x))
+        .find(|s| s.parse::<f64>() == Ok(x))
+        .map(|s| split_exponent(&s))
+        .unwrap();
+    let exp: i32 = exponent.parse().unwrap();
+    if !(-4..16).contains(&exp) {
+        return format!("{}e{}", mantissa, exponent);
+    }
+    // else
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa.as_str()),
+    };
+    let digits = mantissa.replace('.', "");
+    if exp < 0 {
+        format!("{}0.{}{}", sign, "0".repeat((-exp - 1) as usize), digits)
+    } else {
+        let int_len = exp as usize + 1;
+        let digits = format!("{:0<w$}", digits, w = int_len);
+        let (int_part, frac_part) = digits.split_at(int_len);
+        let frac_part = if frac_part.is_empty() { "0" } else { frac_part };
+        format!("{}{}.{}", sign, int_part, frac_part)
+    }
+}
+
+/// Python's `repr()` (or `ascii()` if `ascii` is true) of a value.
+fn repr(value: &Value, ascii: bool) -> String {
+    let s = match value {
+        Value::Str(s) => s,
+        _ => return value.to_string(),
+    };
+    let quote = if s.contains('\'') && !s.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+    let mut result = String::from(quote);
+    for c in s.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            _ if c == quote => {
+                result.push('\\');
+                result.push(c);
+            }
+            ' '..='~' => result.push(c),
+            _ if !c.is_ascii() && !ascii && is_printable_char(c) => result.push(c),
+            _ if (c as u32) < 0x100 => result.push_str(&format!("\\x{:02x}", c as u32)),
+            _ if (c as u32) < 0x10000 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => result.push_str(&format!("\\U{:08x}", c as u32)),
+        }
+    }
+    result.push(quote);
+    result
+}
+
+/// The whitespace characters that separate the words when wrapping (like in Python's `textwrap`).
+fn is_wrap_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r')
+}
+
+/// Splits the text into the chunks of wrapping: words and runs of whitespace.
+/// With `break_on_hyphens`, the compound words are split after their hyphens.
+fn split_chunks(text: &str, break_on_hyphens: bool) -> Vec<String> {
+    let mut chunks: Vec<String> = vec![];
+    for c in text.chars() {
+        match chunks.last_mut() {
+            Some(last) if last.starts_with(is_wrap_whitespace) == is_wrap_whitespace(c) => {
+                last.push(c)
+            }
+            _ => chunks.push(c.to_string()),
+        }
+    }
+    if !break_on_hyphens {
+        return chunks;
+    }
+    // else
+    // the same rules as in Python's textwrap (letters are alphabetic characters and '_')
+    let mut result = vec![];
+    for chunk in chunks {
+        let chars: Vec<char> = chunk.chars().collect();
+        let is = |i: Option<usize>, f: &dyn Fn(char) -> bool| {
+            i.and_then(|i| chars.get(i)).is_some_and(|&c| f(c))
+        };
+        let letter = |c: char| c.is_alphabetic() || c == '_';
+        let word = |c: char| c.is_alphanumeric() || c == '_';
+        let word_punct = |c: char| word(c) || "!\"'&.,?".contains(c);
+        let hyphen = |c: char| c == '-';
+        let mut start = 0;
+        let mut i = 1;
+        while i < chars.len() {
+            if chars[i] != '-' {
+                i += 1;
+                continue;
+            }
+            // an em-dash between words ("a--b")
+            let end = (i..chars.len())
+                .find(|&j| chars[j] != '-')
+                .unwrap_or(chars.len());
+            if end - i >= 2 && is(Some(i - 1), &word_punct) && is(Some(end), &word) {
+                result.push(chars[start..i].iter().collect());
+                result.push(chars[i..end].iter().collect());
+                start = end;
+                i = end;
+                continue;
+            }
+            // a compound word ("long-term", "x-y-zw"), but not "-x" or "a-"
+            let before = is(i.checked_sub(1), &letter)
+                && (is(i.checked_sub(2), &letter)
+                    || (is(i.checked_sub(2), &hyphen) && is(i.checked_sub(3), &letter)));
+            let after = is(Some(i + 1), &letter)
+                && (is(Some(i + 2), &letter)
+                    || (is(Some(i + 2), &hyphen) && is(Some(i + 3), &letter)));
+            if before && after {
+                result.push(chars[start..=i].iter().collect());
+                start = i + 1;
+            }
+            i += 1;
+        }
+        result.push(chars[start..].iter().collect());
+    }
+    result
+}
+
+/// The core of [`wrap`] (the same algorithm as in Python's `textwrap`).
+/// The chunks are in reversed order.
+fn wrap_chunks(mut chunks: Vec<String>, options: &WrapOptions) -> Vec<String> {
+    let is_space = |chunk: &String| chunk.trim().is_empty();
+    if let Some(max_lines) = options.max_lines {
+        let indent = if max_lines > 1 {
+            &options.subsequent_indent
+        } else {
+            &options.initial_indent
+        };
+        assert!(
+            display_width(indent) + display_width(options.placeholder.trim_start())
+                <= options.width,
+            "placeholder too large for max width"
+        );
+    }
+    let mut lines: Vec<String> = vec![];
+    while !chunks.is_empty() {
+        let mut line: Vec<String> = vec![];
+        let mut line_width = 0;
+        let indent = if lines.is_empty() {
+            &options.initial_indent
+        } else {
+            &options.subsequent_indent
+        };
+        let width = options.width.saturating_sub(display_width(indent));
+
+        // the whitespace at the beginning of a line is dropped, except for the first line
+        if options.drop_whitespace && !lines.is_empty() && chunks.last().is_some_and(is_space) {
+            chunks.pop();
+        }
+        while let Some(chunk) = chunks.last() {
+            let chunk_width = display_width(chunk);
+            if line_width + chunk_width > width {
+                break;
+            }
+            line_width += chunk_width;
+            line.push(chunks.pop().unwrap());
+        }
+        if chunks
+            .last()
+            .is_some_and(|chunk| display_width(chunk) > width)
+        {
+            handle_long_word(&mut chunks, &mut line, line_width, width, options);
+            line_width = line.iter().map(|chunk| display_width(chunk)).sum();
+        }
+        if options.drop_whitespace && line.last().is_some_and(is_space) {
+            line_width -= display_width(&line.pop().unwrap());
+        }
+        if line.is_empty() {
+            continue;
+        }
+        // else
+        let only_space_left = chunks.is_empty()
+            || (options.drop_whitespace && chunks.len() == 1 && is_space(&chunks[0]));
+        let fits = match options.max_lines {
+            None => true,
+            Some(max_lines) => {
+                lines.len() + 1 < max_lines || (only_space_left && line_width <= width)
+            }
+        };
+        if fits {
+            lines.push(format!("{}{}", indent, line.concat()));
+            continue;
+        }
+        // else: truncate with the placeholder
+        let placeholder_width = display_width(&options.placeholder);
+        while let Some(last) = line.last() {
+            if !is_space(last) && line_width + placeholder_width <= width {
+                lines.push(format!(
+                    "{}{}{}",
+                    indent,
+                    line.concat(),
+                    options.placeholder
+                ));
+                return lines;
+            }
+            line_width -= display_width(&line.pop().unwrap());
+        }
+        if let Some(previous) = lines.last_mut() {
+            let trimmed = previous.trim_end().to_string();
+            if display_width(&trimmed) + placeholder_width <= options.width {
+                *previous = trimmed + &options.placeholder;
+                return lines;
+            }
+        }
+        lines.push(format!("{}{}", indent, options.placeholder.trim_start()));
+        return lines;
+    }
+    lines
+}
+
+/// Puts (a part of) a word that is too long on the current line.
+fn handle_long_word(
+    chunks: &mut Vec<String>,
+    line: &mut Vec<String>,
+    line_width: usize,
+    width: usize,
+    options: &WrapOptions,
+) {
+    let space_left = if width < 1 { 1 } else { width - line_width };
+    if options.break_long_words {
+        let chunk = chunks.last_mut().unwrap();
+        // the longest prefix that fits (on an empty line at least one character, even if it's wide)
+        let mut end = 0;
+        let mut end_width = 0;
+        for (i, c) in chunk.char_indices() {
+            end_width += UnicodeWidthChar::width(c).unwrap_or(0);
+            if end_width > space_left && (end > 0 || !line.is_empty()) {
+                break;
+            }
+            end = i + c.len_utf8();
+        }
+        if options.break_on_hyphens {
+            // break after the last hyphen that fits, if there's something before it
+            if let Some(hyphen) = chunk[..end].rfind('-') {
+                if hyphen > 0 && chunk[..hyphen].contains(|c| c != '-') {
+                    end = hyphen + 1;
+                }
+            }
+        }
+        let rest = chunk.split_off(end);
+        line.push(std::mem::replace(chunk, rest));
+    } else if line.is_empty() {
+        line.push(chunks.pop().unwrap());
+    }
+}
+
+/// The longest common prefix of two strings.
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i);
+    &a[..len]
+}
+
+/// The characters that stand for a single digit, but are not decimal characters
+/// (e.g. superscripts, circled digits).
+const DIGITS: [(char, char); 20] = [
+    ('\u{b2}', '\u{b3}'),
+    ('\u{b9}', '\u{b9}'),
+    ('\u{1369}', '\u{1371}'),
+    ('\u{19da}', '\u{19da}'),
+    ('\u{2070}', '\u{2070}'),
+    ('\u{2074}', '\u{2079}'),
+    ('\u{2080}', '\u{2089}'),
+    ('\u{2460}', '\u{2468}'),
+    ('\u{2474}', '\u{247c}'),
+    ('\u{2488}', '\u{2490}'),
+    ('\u{24ea}', '\u{24ea}'),
+    ('\u{24f5}', '\u{24fd}'),
+    ('\u{24ff}', '\u{24ff}'),
+    ('\u{2776}', '\u{277e}'),
+    ('\u{2780}', '\u{2788}'),
+    ('\u{278a}', '\u{2792}'),
+    ('\u{10a40}', '\u{10a43}'),
+    ('\u{10e60}', '\u{10e68}'),
+    ('\u{11052}', '\u{1105a}'),
+    ('\u{1f100}', '\u{1f10a}'),
+];
+
+/// The CJK ideographs that are used as numerals (they are letters, not numbers,
+/// in the Unicode categories, but they have a numeric value). These are the
+/// characters with a `kPrimaryNumeric`, `kAccountingNumeric` or `kOtherNumeric`
+/// value in the Unihan database (Unicode 14.0, the same set as in Python 3.11).
+/// The compatibility ideographs are separate code points from the unified ones
+/// they normalize to, so they're listed with their canonical equivalents.
+const CJK_NUMERALS: [char; 81] = [
+    // CJK Unified Ideographs Extension A
+    '\u{3405}', // 㐅
+    '\u{3483}', // 㒃
+    '\u{382a}', // 㠪
+    '\u{3b4d}', // 㭍
+    // CJK Unified Ideographs
+    '\u{4e00}', // 一
+    '\u{4e03}', // 七
+    '\u{4e07}', // 万
+    '\u{4e09}', // 三
+    '\u{4e5d}', // 九
+    '\u{4e8c}', // 二
+    '\u{4e94}', // 五
+    '\u{4e96}', // 亖
+    '\u{4ebf}', // 亿
+    '\u{4ec0}', // 什
+    '\u{4edf}', // 仟
+    '\u{4ee8}', // 仨
+    '\u{4f0d}', // 伍
+    '\u{4f70}', // 佰
+    '\u{5104}', // 億
+    '\u{5146}', // 兆
+    '\u{5169}', // 兩
+    '\u{516b}', // 八
+    '\u{516d}', // 六
+    '\u{5341}', // 十
+    '\u{5343}', // 千
+    '\u{5344}', // 卄
+    '\u{5345}', // 卅
+    '\u{534c}', // 卌
+    '\u{53c1}', // 叁
+    '\u{53c2}', // 参
+    '\u{53c3}', // 參
+    '\u{53c4}', // 叄
+    '\u{56db}', // 四
+    '\u{58f1}', // 壱
+    '\u{58f9}', // 壹
+    '\u{5e7a}', // 幺
+    '\u{5efe}', // 廾
+    '\u{5eff}', // 廿
+    '\u{5f0c}', // 弌
+    '\u{5f0d}', // 弍
+    '\u{5f0e}', // 弎
+    '\u{5f10}', // 弐
+    '\u{62fe}', // 拾
+    '\u{634c}', // 捌
+    '\u{67d2}', // 柒
+    '\u{6f06}', // 漆
+    '\u{7396}', // 玖
+    '\u{767e}', // 百
+    '\u{8086}', // 肆
+    '\u{842c}', // 萬
+    '\u{8cae}', // 貮
+    '\u{8cb3}', // 貳
+    '\u{8d30}', // 贰
+    '\u{9621}', // 阡
+    '\u{9646}', // 陆
+    '\u{964c}', // 陌
+    '\u{9678}', // 陸
+    '\u{96f6}', // 零
+    // CJK Compatibility Ideographs
+    '\u{f96b}', // 參 (U+53C3)
+    '\u{f973}', // 拾 (U+62FE)
+    '\u{f978}', // 兩 (U+5169)
+    '\u{f9b2}', // 零 (U+96F6)
+    '\u{f9d1}', // 六 (U+516D)
+    '\u{f9d3}', // 陸 (U+9678)
+    '\u{f9fd}', // 什 (U+4EC0)
+    // CJK Unified Ideographs Extension B
+    '\u{20001}', // 𠀁
+    '\u{20064}', // 𠁤
+    '\u{200e2}', // 𠃢
+    '\u{20121}', // 𠄡
+    '\u{2092a}', // 𠤪
+    '\u{20983}', // 𠦃
+    '\u{2098c}', // 𠦌
+    '\u{2099c}', // 𠦜
+    '\u{20aea}', // 𠫪
+    '\u{20afd}', // 𠫽
+    '\u{20b19}', // 𠬙
+    '\u{22390}', // 𢎐
+    '\u{22998}', // 𢦘
+    '\u{23b1b}', // 𣬛
+    '\u{2626d}', // 𦉭
+    // CJK Compatibility Ideographs Supplement
+    '\u{2f890}', // 廾 (U+5EFE)
+];
+
+/// The special case foldings (besides lowercasing).
+const CASEFOLD: [(char, &str); 36] = [
+    ('\u{b5}', "μ"),                // µ
+    ('\u{df}', "ss"),               // ß
+    ('\u{149}', "ʼn"),              // ŉ
+    ('\u{17f}', "s"),               // ſ
+    ('\u{1f0}', "j\u{30c}"),        // ǰ
+    ('\u{345}', "ι"),               // combining ypogegrammeni
+    ('\u{390}', "ι\u{308}\u{301}"), // ΐ
+    ('\u{3b0}', "υ\u{308}\u{301}"), // ΰ
+    ('\u{3c2}', "σ"),               // ς
+    ('\u{3d0}', "β"),               // ϐ
+    ('\u{3d1}', "θ"),               // ϑ
+    ('\u{3d5}', "φ"),               // ϕ
+    ('\u{3d6}', "π"),               // ϖ
+    ('\u{3f0}', "κ"),               // ϰ
+    ('\u{3f1}', "ρ"),               // ϱ
+    ('\u{3f5}', "ε"),               // ϵ
+    ('\u{587}', "եւ"),              // և
+    ('\u{1e96}', "h\u{331}"),       // ẖ
+    ('\u{1e97}', "t\u{308}"),       // ẗ
+    ('\u{1e98}', "w\u{30a}"),       // ẘ
+    ('\u{1e99}', "y\u{30a}"),       // ẙ
+    ('\u{1e9a}', "aʾ"),             // ẚ
+    ('\u{1e9b}', "ṡ"),              // ẛ
+    ('\u{1e9e}', "ss"),             // ẞ
+    ('\u{fb00}', "ff"),             // ﬀ
+    ('\u{fb01}', "fi"),             // ﬁ
+    ('\u{fb02}', "fl"),             // ﬂ
+    ('\u{fb03}', "ffi"),            // ﬃ
+    ('\u{fb04}', "ffl"),            // ﬄ
+    ('\u{fb05}', "st"),             // ﬅ
+    ('\u{fb06}', "st"),             // ﬆ
+    ('\u{fb13}', "մն"),             // ﬓ
+    ('\u{fb14}', "մե"),             // ﬔ
+    ('\u{fb15}', "մի"),             // ﬕ
+    ('\u{fb16}', "վն"),             // ﬖ
+    ('\u{fb17}', "մխ"),             // ﬗ
+];
+
+// ==========================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn str_rev_test1() {
+        assert_eq!(str_rev(""), "");
+        assert_eq!(str_rev("a"), "a");
+        assert_eq!(str_rev("ab"), "ba");
+        assert_eq!(str_rev("anna"), "anna");
+        assert_eq!(str_rev("abc"), "cba");
+        assert_eq!(str_rev("AbCdE"), "EdCbA");
+    }
+
+    #[test]
+    fn is_palindrome_test1() {
+        assert!(is_palindrome(""));
+        assert!(is_palindrome("a"));
+        assert!(is_palindrome("aa"));
+        assert!(is_palindrome("anna"));
+        assert!(is_palindrome("görög"));
+    }
+
+    #[test]
+    fn is_palindrome_test2() {
+        assert_eq!(is_palindrome("ab"), false);
+        assert_eq!(is_palindrome("Anna"), false);
+    }
+
+    #[test]
+    fn chomp_test1() {
+        let mut text = "".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "");
+        //
+        let mut text = "abc".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "abc");
+        //
+        let mut text = "\n".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "");
+        //
+        let mut text = "\r\n".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "");
+        //
+        let mut text = "\nend".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "\nend");
+        //
+        let mut text = "\r\nend".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "\r\nend");
+        //
+        let mut text = "longer\nstring\n".to_string();
+        chomp(&mut text);
+        assert_eq!(text, "longer\nstring");
+    }
+
+    #[test]
+    fn center_test1() {
+        assert_eq!(center("-", 0), "-");
+        assert_eq!(center("-", 1), "-");
+        assert_eq!(center("-", 2), "- ");
+        assert_eq!(center("-", 3), " - ");
+    }
+
+    #[test]
+    fn str_rev_test2() {
+        assert_eq!(str_rev("a\u{308}o"), "oa\u{308}");
+        assert_eq!(str_rev("x🇭🇺🇩🇪"), "🇩🇪🇭🇺x");
+        assert_eq!(str_rev("ab👩‍💻"), "👩‍💻ba");
+        assert_eq!(str_rev("a\r\nb"), "b\r\na");
+    }
+
+    #[test]
+    fn is_palindrome_with_test1() {
+        let loose = PalindromeOptions::loose();
+        assert!(is_palindrome_with("", loose));
+        assert!(is_palindrome_with("!?", loose));
+        assert!(is_palindrome_with("A man, a plan, a canal: Panama", loose));
+        assert!(is_palindrome_with("Indul a görög aludni.", loose));
+        assert!(is_palindrome_with("Ésé", loose));
+        assert!(!is_palindrome_with("abca", loose));
+        //
+        let default = PalindromeOptions::default();
+        assert!(is_palindrome_with("görög", default));
+        assert!(is_palindrome_with("go\u{308}ro\u{308}g", default));
+        assert!(!is_palindrome_with("Görög", default));
+        //
+        let case = PalindromeOptions {
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert!(is_palindrome_with("Görög", case));
+        assert!(!is_palindrome_with("Gö rög", case));
+        //
+        let punctuation = PalindromeOptions {
+            ignore_punctuation: true,
+            ..Default::default()
+        };
+        assert!(is_palindrome_with("gö, rög!", punctuation));
+        assert!(!is_palindrome_with("Gö, rög!", punctuation));
+        //
+        let diacritics = PalindromeOptions {
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        assert!(is_palindrome_with("góróg", diacritics));
+        assert!(!is_palindrome_with("Görög", diacritics));
+    }
+
+    #[test]
+    fn center_test2() {
+        assert_eq!(center("", 2), "  ");
+        assert_eq!(center("日", 1), "日");
+        assert_eq!(center("日", 3), "日 ");
+        assert_eq!(center("日本", 7), " 日本  ");
+        assert_eq!(center("e\u{301}", 3), " e\u{301} ");
+    }
+
+    #[test]
+    fn display_width_test1() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("Árvíz"), 5);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("ｱ"), 1);
+        assert_eq!(display_width("a\u{200b}b"), 2);
     }
 
     #[test]
@@ -177,4 +2569,572 @@ mod tests {
         assert_eq!(capitalize("aa"), "Aa");
         assert_eq!(capitalize("aNnA"), "Anna");
     }
+
+    #[test]
+    fn ljust_rjust_test1() {
+        assert_eq!(ljust("", 3, '-'), "---");
+        assert_eq!(ljust("ab", 3, ' '), "ab ");
+        assert_eq!(ljust("áé", 4, 'ő'), "áéőő");
+        assert_eq!(ljust("abc", 1, '-'), "abc");
+        assert_eq!(rjust("", 3, '-'), "---");
+        assert_eq!(rjust("áé", 4, 'ő'), "őőáé");
+        assert_eq!(rjust("abc", 3, '-'), "abc");
+        assert_eq!(ljust("日本", 5, '-'), "日本-");
+        assert_eq!(rjust("日本", 5, '-'), "-日本");
+        assert_eq!(ljust("a", 5, '日'), "a日日");
+    }
+
+    #[test]
+    fn zfill_test1() {
+        assert_eq!(zfill("", 3), "000");
+        assert_eq!(zfill("7", 3), "007");
+        assert_eq!(zfill("+7", 3), "+07");
+        assert_eq!(zfill("-", 3), "-00");
+        assert_eq!(zfill("12345", 3), "12345");
+        assert_eq!(zfill("a-1", 5), "00a-1");
+    }
+
+    #[test]
+    fn title_swapcase_test1() {
+        assert_eq!(title(""), "");
+        assert_eq!(title("ÁRVÍZTŰRŐ tükörfúrógép"), "Árvíztűrő Tükörfúrógép");
+        assert_eq!(title("abc1def x_y"), "Abc1Def X_Y");
+        assert_eq!(title("ǆemal"), "ǅemal");
+        assert_eq!(swapcase(""), "");
+        assert_eq!(swapcase("aBc 1 ÁrVíZ"), "AbC 1 áRvÍz");
+        assert_eq!(swapcase("ß"), "SS");
+    }
+
+    #[test]
+    fn partition_test1() {
+        assert_eq!(partition("", "->"), ("", "", ""));
+        assert_eq!(partition("a->b->c", "->"), ("a", "->", "b->c"));
+        assert_eq!(partition("->", "->"), ("", "->", ""));
+        assert_eq!(rpartition("", "->"), ("", "", ""));
+        assert_eq!(rpartition("a->b->c", "->"), ("a->b", "->", "c"));
+        assert_eq!(rpartition("ő€ű", "€"), ("ő", "€", "ű"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn partition_test2() {
+        partition("abc", "");
+    }
+
+    #[test]
+    fn expandtabs_test1() {
+        assert_eq!(expandtabs("", 8), "");
+        assert_eq!(expandtabs("\ta", 8), "        a");
+        assert_eq!(expandtabs("ab\tc\nd\te", 4), "ab  c\nd   e");
+        assert_eq!(expandtabs("é\tx", 2), "é x");
+        assert_eq!(expandtabs("a\tb", 0), "ab");
+    }
+
+    #[test]
+    fn splitlines_test1() {
+        assert_eq!(splitlines("", false), Vec::<&str>::new());
+        assert_eq!(splitlines("\n", false), [""]);
+        assert_eq!(splitlines("a", true), ["a"]);
+        assert_eq!(splitlines("a\r\n\rb\u{2028}c", false), ["a", "", "b", "c"]);
+        assert_eq!(
+            splitlines("a\r\n\rb\u{2028}c", true),
+            ["a\r\n", "\r", "b\u{2028}", "c"]
+        );
+        assert_eq!(
+            splitlines("a\x0bb\x0cc\x1cd\u{85}e", false),
+            ["a", "b", "c", "d", "e"]
+        );
+    }
+
+    #[test]
+    fn removeprefix_removesuffix_test1() {
+        assert_eq!(removeprefix("", "ab"), "");
+        assert_eq!(removeprefix("abab", "ab"), "ab");
+        assert_eq!(removeprefix("abab", ""), "abab");
+        assert_eq!(removesuffix("", "ab"), "");
+        assert_eq!(removesuffix("abab", "ab"), "ab");
+        assert_eq!(removesuffix("abab", "ba"), "abab");
+    }
+
+    #[test]
+    fn casefold_test1() {
+        assert_eq!(casefold(""), "");
+        assert_eq!(casefold("ÁRVÍZ"), "árvíz");
+        assert_eq!(casefold("ẞ"), "ss");
+        assert_eq!(casefold("ΣΊΣΥΦΟΣ"), casefold("σίσυφος"));
+        assert_eq!(casefold("ﬃ"), "ffi");
+        assert_eq!(casefold("µ"), casefold("Μ"));
+    }
+
+    #[test]
+    fn isidentifier_test1() {
+        assert!(isidentifier("a"));
+        assert!(isidentifier("_"));
+        assert!(isidentifier("__init__"));
+        assert!(isidentifier("árvíz2"));
+        assert!(!isidentifier(""));
+        assert!(!isidentifier("1a"));
+        assert!(!isidentifier("a-b"));
+        assert!(!isidentifier("a b"));
+    }
+
+    #[test]
+    fn isdigit_and_friends_test1() {
+        assert!(isdecimal("0123456789٣"));
+        assert!(!isdecimal("²"));
+        assert!(!isdecimal(""));
+        assert!(isdigit("0123456789٣²₃①⓿"));
+        assert!(!isdigit("½"));
+        assert!(!isdigit(""));
+        assert!(isnumeric("0123456789٣²₃①⓿½Ⅻ五百億"));
+        assert!(!isnumeric("a"));
+        assert!(!isnumeric(""));
+        // the compatibility ideographs (U+F96B, U+2F890) and the unified ones
+        assert!(isnumeric("\u{f96b}\u{53c3}\u{2f890}\u{5efe}"));
+        assert!(!isnumeric("\u{f96c}"));
+        // the table is searched with binary_search
+        assert!(CJK_NUMERALS.windows(2).all(|w| w[0] < w[1]));
+        assert!(isalpha("ÁrvízǅΣ日本"));
+        assert!(!isalpha("a1"));
+        assert!(!isalpha(""));
+        assert!(isalnum("Árvíz2½五"));
+        assert!(!isalnum("a_1"));
+        assert!(!isalnum(""));
+    }
+
+    #[test]
+    fn isspace_test1() {
+        assert!(isspace(" \t\n\r\x0b\x0c\x1c\x1f\u{85}\u{a0}\u{3000}"));
+        assert!(!isspace(""));
+        assert!(!isspace(" a "));
+        assert!(!isspace("\u{200b}"));
+    }
+
+    #[test]
+    fn islower_isupper_istitle_test1() {
+        assert!(islower("árvíz 2"));
+        assert!(!islower("Árvíz"));
+        assert!(!islower("ǅ"));
+        assert!(!islower("123"));
+        assert!(isupper("ÁRVÍZ 2"));
+        assert!(!isupper("ÁRVÍz"));
+        assert!(!isupper("123"));
+        assert!(istitle("Árvíz Tűrő"));
+        assert!(istitle("ǅemal"));
+        assert!(istitle("A1B C_D"));
+        assert!(!istitle("ÁRvíz"));
+        assert!(!istitle("árvíz"));
+        assert!(!istitle("123"));
+        assert!(!istitle(""));
+    }
+
+    #[test]
+    fn isascii_isprintable_test1() {
+        assert!(isascii(""));
+        assert!(isascii("abc\x7f"));
+        assert!(!isascii("é"));
+        assert!(isprintable(""));
+        assert!(isprintable("Árvíz tűrő!"));
+        assert!(!isprintable("a\tb"));
+        assert!(!isprintable("\u{a0}"));
+        assert!(!isprintable("\u{200b}"));
+    }
+
+    #[test]
+    fn count_test1() {
+        assert_eq!(count("", "", false), 1);
+        assert_eq!(count("ábc", "", false), 4);
+        assert_eq!(count("ábc", "", true), 4);
+        assert_eq!(count("ababab", "aba", false), 1);
+        assert_eq!(count("ababab", "aba", true), 2);
+        assert_eq!(count("őőőő", "őő", false), 2);
+        assert_eq!(count("őőőő", "őő", true), 3);
+        assert_eq!(count("abc", "x", true), 0);
+    }
+
+    #[test]
+    fn slice_test1() {
+        assert_eq!(slice("", None, None, 1), "");
+        assert_eq!(slice("", Some(-5), Some(5), -1), "");
+        assert_eq!(slice("abcdef", Some(1), Some(3), 1), "bc");
+        assert_eq!(slice("abcdef", Some(-2), None, 1), "ef");
+        assert_eq!(slice("abcdef", None, None, 2), "ace");
+        assert_eq!(slice("abcdef", None, None, -2), "fdb");
+        assert_eq!(slice("abcdef", Some(3), None, isize::MAX), "d");
+        assert_eq!(slice("abcdef", None, None, isize::MIN), "f");
+        assert_eq!(slice("abcdef", Some(4), Some(1), -1), "edc");
+        assert_eq!(slice("abcdef", Some(99), Some(-99), -1), "fedcba");
+        assert_eq!(slice("abcdef", Some(3), Some(3), 1), "");
+        assert_eq!(slice("őű", Some(1), None, 1), "ű");
+        assert_eq!(slice("abc", None, None, -1), str_rev("abc"));
+    }
+
+    #[test]
+    fn slice_graphemes_test1() {
+        assert_eq!(slice_graphemes("", None, None, -1), "");
+        assert_eq!(slice_graphemes("abc", Some(-2), None, 1), "bc");
+        assert_eq!(slice_graphemes("a\r\nb", None, None, -1), "b\r\na");
+        assert_eq!(slice_graphemes("🇭🇺🇩🇪", Some(1), None, 1), "🇩🇪");
+        assert_eq!(slice_graphemes("n\u{303}o", None, None, -1), "on\u{303}");
+    }
+
+    fn format_args() -> (Vec<Value>, HashMap<&'static str, Value>) {
+        let args = vec![
+            Value::from(1.23456),
+            Value::from(1234567),
+            Value::from(-42),
+            Value::from("Éva"),
+            Value::from(true),
+            Value::from(f64::INFINITY),
+        ];
+        let kwargs = HashMap::from([
+            ("name", Value::from("Éva")),
+            ("width", Value::from(8)),
+            ("x", Value::from(-1234.5678)),
+        ]);
+        (args, kwargs)
+    }
+
+    #[test]
+    fn format_test1() {
+        let (args, kwargs) = format_args();
+        let f = |template| format(template, &args, &kwargs).unwrap();
+        assert_eq!(f(""), "");
+        assert_eq!(f("no fields"), "no fields");
+        assert_eq!(f("{{}} {{{name}}} }}{{"), "{} {Éva} }{");
+        assert_eq!(f("{}|{}|{}|{}|{}|{}"), "1.23456|1234567|-42|Éva|True|inf");
+        assert_eq!(f("{0}{0}{2}"), "1.234561.23456-42");
+        assert_eq!(f("{name:>{width}}|{name:{width}.{1}}"), "     Éva|Éva     ");
+    }
+
+    #[test]
+    fn format_test2() {
+        // floats, the expected values come from Python
+        let (args, kwargs) = format_args();
+        let f = |template| format(template, &args, &kwargs).unwrap();
+        assert_eq!(
+            f("{0:.2f}|{0:10.3f}|{0:<10.3}|{0:^+10.1e}|{0:g}|{0:.0f}|{0:#.0f}|{0:.3%}|{0:E}"),
+            "1.23|     1.235|1.23      | +1.2e+00 |1.23456|1|1.|123.456%|1.234560E+00"
+        );
+        assert_eq!(
+            f("{x:,.2f}|{x:015,.2f}|{x:=12}|{x:^16.1f}|{x:.3}|{x}|{x:.15e}|{x:.17g}"),
+            "-1,234.57|-000,001,234.57|-  1234.5678|    -1234.6     |-1.23e+03|-1234.5678|-1.234567800000000e+03|-1234.5678"
+        );
+        assert_eq!(f("{5}|{5:+F}|{5:>6}|{5:010}"), "inf|+INF|   inf|0000000inf");
+        let f = |template, x: f64| format(template, &[x.into()], &kwargs).unwrap();
+        assert_eq!(
+            f("{0}|{0:.3}|{0:.1}|{0:g}|{0:#g}|{0:.0e}", 100.0),
+            "100.0|1e+02|1e+02|100|100.000|1e+02"
+        );
+        assert_eq!(
+            f("{0}|{0:f}|{0:g}|{0:.3}", 1e20),
+            "1e+20|100000000000000000000.000000|1e+20|1e+20"
+        );
+        assert_eq!(
+            f("{0}|{0:f}|{0:g}|{0:.2e}", 1.5e-7),
+            "1.5e-07|0.000000|1.5e-07|1.50e-07"
+        );
+        assert_eq!(f("{0}|{0:f}|{0:+g}", -0.0), "-0.0|-0.000000|-0");
+        assert_eq!(f("{0:.0f}|{0:.0%}", 0.5), "0|50%");
+        assert_eq!(f("{0:.0f}", 2.5), "2");
+        assert_eq!(f("{0}", 1114673615909095.2), "1114673615909095.2");
+        assert_eq!(f("{0}|{0:E}", f64::NAN), "nan|NAN");
+        assert_eq!(
+            f("{0:%}|{0:.2%}|{0:>6%}|{0:+%}|{0:08%}", f64::INFINITY),
+            "inf%|inf%|  inf%|+inf%|0000inf%"
+        );
+        assert_eq!(f("{0:%}|{0:.3%}", -f64::NAN), "nan%|nan%");
+        assert_eq!(f("{0:.1%}", f64::NEG_INFINITY), "-inf%");
+    }
+
+    #[test]
+    fn format_test3() {
+        // integers and bools
+        let (args, kwargs) = format_args();
+        let f = |template| format(template, &args, &kwargs).unwrap();
+        assert_eq!(
+            f("{1:,}|{1:_}|{1:>15,}|{1:015,}|{1:e}|{1:.3g}|{1:%}"),
+            "1,234,567|1_234_567|      1,234,567|000,001,234,567|1.234567e+06|1.23e+06|123456700.000000%"
+        );
+        assert_eq!(
+            f("{2:+}|{2: }|{2:05}|{2:=+8}|{2:*^9}|{2:x}|{2:#x}|{2:#b}|{2:#o}|{2:#X}|{2:08b}"),
+            "-42|-42|-0042|-     42|***-42***|-2a|-0x2a|-0b101010|-0o52|-0X2A|-0101010"
+        );
+        let f = |template, n: i64| format(template, &[n.into()], &kwargs).unwrap();
+        assert_eq!(
+            f(
+                "{0:c}|{0:_b}|{0:#010x}|{0:_x}|{0:n}|{0:3}|{0:<5}|{0:.2f}|{0: d}",
+                255
+            ),
+            "ÿ|1111_1111|0x000000ff|ff|255|255|255  |255.00| 255"
+        );
+        assert_eq!(f("{0:05}|{0:<05}|{0:^05}", -3), "-0003|-3000|0-300");
+        assert_eq!(f("{0:,}", i64::MIN), "-9,223,372,036,854,775,808");
+        let f = |template, b: bool| format(template, &[b.into()], &kwargs).unwrap();
+        assert_eq!(
+            f("{0}|{0:>6}|{0:d}|{0!r}|{0:.1f}|{0!s:>6}", true),
+            "True|     1|1|True|1.0|  True"
+        );
+    }
+
+    #[test]
+    fn format_test4() {
+        // strings and conversions
+        let (args, kwargs) = format_args();
+        let f = |template| format(template, &args, &kwargs).unwrap();
+        assert_eq!(
+            f("{3:>6}|{3:*<6}|{3:^7}|{3:.2}|{3!r}|{3!a}|{3!s:>5}|{3:s}"),
+            "   Éva|Éva***|  Éva  |Év|'Éva'|'\\xc9va'|  Éva|Éva"
+        );
+        assert_eq!(f("{0!r}|{0!s:.3}"), "1.23456|1.2");
+        assert_eq!(f("[{:^6}]"), "[1.23456]");
+        let f = |template, s: &str| format(template, &[s.into()], &kwargs).unwrap();
+        let text = "it's \"q\"\n\t\\é\x01\u{200b}😀";
+        assert_eq!(f("{0!r}", text), r#"'it\'s "q"\n\t\\é\x01\u200b😀'"#);
+        assert_eq!(
+            f("{0!a}", text),
+            r#"'it\'s "q"\n\t\\\xe9\x01\u200b\U0001f600'"#
+        );
+        assert_eq!(f("{0!r}", "it's"), r#""it's""#);
+        assert_eq!(f("{0:>4}|{0:-<4}", "日本"), "日本|日本");
+        assert_eq!(f("{0:>5}|{0:-<5}", "日本"), " 日本|日本-");
+        // the zero flag pads strings on the right (like in Python 3.10+)
+        assert_eq!(
+            f("{0:05}|{0:<05}|{0:^05}|{0:x>05}|{0:05.1}|{0:05s}", "ab"),
+            "ab000|ab000|0ab00|xxxab|a0000|ab000"
+        );
+    }
+
+    #[test]
+    fn format_test5() {
+        // errors
+        let (args, kwargs) = format_args();
+        let f = |template| format(template, &args, &kwargs).unwrap_err();
+        let spec = |spec: &str, reason| FormatError::InvalidSpec {
+            spec: spec.to_string(),
+            reason,
+        };
+        assert_eq!(f("ab{"), FormatError::UnmatchedBrace(2));
+        assert_eq!(f("ab}c"), FormatError::UnmatchedBrace(2));
+        assert_eq!(f("{0"), FormatError::UnmatchedBrace(0));
+        assert_eq!(
+            f("{name.x}"),
+            FormatError::InvalidFieldName("name.x".to_string())
+        );
+        assert_eq!(
+            f("{0[1]}"),
+            FormatError::InvalidFieldName("0[1]".to_string())
+        );
+        assert_eq!(f("{}{0}"), FormatError::MixedNumbering);
+        assert_eq!(f("{0}{}"), FormatError::MixedNumbering);
+        assert_eq!(f("{99}"), FormatError::MissingPositional(99));
+        assert_eq!(f("{}{}{}{}{}{}{}"), FormatError::MissingPositional(6));
+        assert_eq!(f("{age}"), FormatError::MissingNamed("age".to_string()));
+        assert_eq!(
+            f("{0:{nope}}"),
+            FormatError::MissingNamed("nope".to_string())
+        );
+        assert_eq!(f("{3!x}"), FormatError::InvalidConversion("x".to_string()));
+        assert_eq!(
+            f("{3!rr}"),
+            FormatError::InvalidConversion("rr".to_string())
+        );
+        let unknown = |code, type_name| FormatError::UnknownFormatCode { code, type_name };
+        assert_eq!(f("{3:d}"), unknown('d', "str"));
+        assert_eq!(f("{0:d}"), unknown('d', "float"));
+        assert_eq!(f("{0:,b}"), unknown('b', "float"));
+        assert_eq!(f("{1:s}"), unknown('s', "int"));
+        assert_eq!(f("{4:s}"), unknown('s', "bool"));
+        assert_eq!(f("{3:+}"), spec("+", "sign not allowed with strings"));
+        assert_eq!(
+            f("{3:=5}"),
+            spec("=5", "'=' alignment not allowed with strings")
+        );
+        assert_eq!(f("{3:,}"), spec(",", "grouping not allowed with strings"));
+        assert_eq!(
+            f("{3:#}"),
+            spec("#", "alternate form (#) not allowed with strings")
+        );
+        assert_eq!(
+            f("{2:.2}"),
+            spec(".2", "precision not allowed with integers")
+        );
+        assert_eq!(
+            f("{2:,x}"),
+            spec(",x", "',' grouping not allowed with 'b', 'o', 'x' and 'X'")
+        );
+        assert_eq!(f("{2:c}"), spec("c", "the value is not a valid character"));
+        assert_eq!(f("{0:xx}"), spec("xx", "unexpected characters"));
+        assert_eq!(f("{0:.}"), spec(".", "missing precision"));
+        assert_eq!(f("{0:{{}}}"), spec("{}", "unexpected characters"));
+        assert_eq!(
+            f("{0:{width:{width}}}"),
+            spec("{width}", "too deeply nested fields")
+        );
+        assert_eq!(f("{9}").to_string(), "missing positional argument: 9");
+    }
+
+    #[test]
+    fn wrap_test1() {
+        let text = "The quick brown fox jumps over the lazy dog.";
+        assert_eq!(wrap("", &WrapOptions::new(10)), Vec::<String>::new());
+        assert_eq!(wrap("   ", &WrapOptions::new(10)), Vec::<String>::new());
+        assert_eq!(wrap(text, &WrapOptions::default()), [text]);
+        assert_eq!(
+            wrap(text, &WrapOptions::new(10)),
+            ["The quick", "brown fox", "jumps over", "the lazy", "dog."]
+        );
+        assert_eq!(
+            wrap(text, &WrapOptions::new(2))[..5],
+            ["Th", "e ", "qu", "ic", "k "]
+        );
+        assert_eq!(
+            wrap("  leading   spaces  ", &WrapOptions::new(10)),
+            ["  leading", "spaces"]
+        );
+        assert_eq!(
+            wrap("tabs\tand\nnewlines", &WrapOptions::new(15)),
+            ["tabs    and", "newlines"]
+        );
+    }
+
+    #[test]
+    fn wrap_test2() {
+        // the expected values come from Python
+        let text = "A well-known long-term state-of-the-art x-ray -flag flag- a--b";
+        assert_eq!(
+            wrap(text, &WrapOptions::new(8)),
+            [
+                "A well-", "known", "long-", "term", "state-", "of-the-", "art", "x-ray", "-flag",
+                "flag- a", "--b"
+            ]
+        );
+        assert_eq!(
+            wrap(text, &WrapOptions::new(3))[7..13],
+            ["sta", "te-", "of-", "the", "-", "art"]
+        );
+        let options = WrapOptions {
+            width: 10,
+            break_on_hyphens: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            wrap(text, &options)[..4],
+            ["A", "well-known", "long-term ", "state-of-t"]
+        );
+        let options = WrapOptions {
+            width: 5,
+            break_long_words: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            wrap("Supercalifragilistic is a word", &options),
+            ["Supercalifragilistic", "is a", "word"]
+        );
+    }
+
+    #[test]
+    fn wrap_test3() {
+        let text = "The quick brown fox jumps over the lazy dog.";
+        let options = WrapOptions {
+            width: 12,
+            initial_indent: "* ".to_string(),
+            subsequent_indent: "  ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            wrap(text, &options),
+            [
+                "* The quick",
+                "  brown fox",
+                "  jumps over",
+                "  the lazy",
+                "  dog."
+            ]
+        );
+        let options = WrapOptions {
+            width: 15,
+            max_lines: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(wrap(text, &options), ["The quick brown", "fox jumps [...]"]);
+        let options = WrapOptions {
+            width: 12,
+            max_lines: Some(1),
+            placeholder: "...".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(wrap(text, &options), ["The quick..."]);
+        let options = WrapOptions {
+            width: 15,
+            drop_whitespace: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            wrap(text, &options),
+            ["The quick brown", " fox jumps over", " the lazy dog."]
+        );
+    }
+
+    #[test]
+    fn wrap_test4() {
+        // East Asian wide characters take up two columns
+        assert_eq!(
+            wrap("日本語 東京 大阪 京都", &WrapOptions::new(10)),
+            ["日本語", "東京 大阪", "京都"]
+        );
+        assert_eq!(wrap("日本語", &WrapOptions::new(3)), ["日", "本", "語"]);
+        assert_eq!(wrap("日本語", &WrapOptions::new(1)), ["日", "本", "語"]);
+        assert_eq!(
+            wrap("Árvíztűrő tükörfúrógép", &WrapOptions::new(10)),
+            ["Árvíztűrő ", "tükörfúróg", "ép"]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrap_test5() {
+        wrap("text", &WrapOptions::new(0));
+    }
+
+    #[test]
+    fn fill_test1() {
+        assert_eq!(fill("", &WrapOptions::new(5)), "");
+        assert_eq!(fill("aaa bbb ccc", &WrapOptions::new(7)), "aaa bbb\nccc");
+    }
+
+    #[test]
+    fn shorten_test1() {
+        let text = "Hello  world!  This is   a test.";
+        assert_eq!(shorten("", 10, " [...]"), "");
+        assert_eq!(shorten(text, 40, " [...]"), "Hello world! This is a test.");
+        assert_eq!(shorten(text, 20, " [...]"), "Hello world! [...]");
+        assert_eq!(shorten(text, 11, " [...]"), "Hello [...]");
+        assert_eq!(shorten(text, 8, " [...]"), "[...]");
+        assert_eq!(shorten(text, 8, "…"), "Hello…");
+    }
+
+    #[test]
+    #[should_panic]
+    fn shorten_test2() {
+        shorten("Hello world!", 4, " [...]");
+    }
+
+    #[test]
+    fn indent_test1() {
+        assert_eq!(indent("", "> "), "");
+        assert_eq!(indent("a", "> "), "> a");
+        assert_eq!(indent("a\n \nb\r\nc\n", "> "), "> a\n \n> b\r\n> c\n");
+    }
+
+    #[test]
+    fn dedent_test1() {
+        assert_eq!(dedent(""), "");
+        assert_eq!(dedent("  a\n    b\n  c"), "a\n  b\nc");
+        assert_eq!(dedent("\ta\n\t\tb"), "a\n\tb");
+        assert_eq!(dedent("  a\n\tb"), "  a\n\tb");
+        assert_eq!(dedent("   \n  a\n    \n  b\n"), "\na\n\nb\n");
+        assert_eq!(dedent("a\n  b"), "a\n  b");
+        assert_eq!(dedent("  \t x\n  \t y"), "x\ny");
+    }
 }
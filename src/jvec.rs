@@ -102,6 +102,93 @@ pub fn is_palindrome<T: PartialEq>(v: &[T]) -> bool {
     true
 }
 
+/// Returns the positions selected by the slice `[start:stop:step]` in a sequence of length `len`.
+///
+/// Negative indices count from the end. Out-of-range indices are clamped (no panic).
+/// `None` means that the index was omitted (e.g. `[::-1]`).
+///
+/// It's like Python's `range(*slice(start, stop, step).indices(len))`.
+/// Panics if `step` is 0 (Python raises a `ValueError`).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(jabba_lib::jvec::slice_indices(5, Some(1), None, 2), [1, 3]);
+/// assert_eq!(jabba_lib::jvec::slice_indices(5, None, Some(-3), -1), [4, 3]);
+/// assert_eq!(jabba_lib::jvec::slice_indices(5, Some(-99), Some(99), 1), [0, 1, 2, 3, 4]);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// assert list(range(*slice(1, None, 2).indices(5))) == [1, 3]
+/// assert list(range(*slice(None, -3, -1).indices(5))) == [4, 3]
+/// assert list(range(*slice(-99, 99, 1).indices(5))) == [0, 1, 2, 3, 4]
+/// ```
+pub fn slice_indices(
+    len: usize,
+    start: Option<isize>,
+    stop: Option<isize>,
+    step: isize,
+) -> Vec<usize> {
+    assert!(step != 0, "slice step cannot be zero");
+    let len = len as isize;
+    // the same clamping as in CPython's PySlice_AdjustIndices()
+    let (lower, upper) = if step > 0 { (0, len) } else { (-1, len - 1) };
+    let adjust = |index: Option<isize>, default: isize| match index {
+        None => default,
+        Some(i) if i < 0 => (i + len).max(lower),
+        Some(i) => i.min(upper),
+    };
+    let start = adjust(start, if step > 0 { lower } else { upper });
+    let stop = adjust(stop, if step > 0 { upper } else { lower });
+
+    let mut result = vec![];
+    let mut i = start;
+    while (step > 0 && i < stop) || (step < 0 && i > stop) {
+        result.push(i as usize);
+        // an overflow means that the next index is beyond stop anyway
+        match i.checked_add(step) {
+            Some(next) => i = next,
+            None => break,
+        }
+    }
+    result
+}
+
+/// Returns the slice `v[start:stop:step]` of an array / vector (as a new vector).
+///
+/// Negative indices count from the end, and a negative step goes backwards.
+/// Out-of-range indices are clamped (no panic). `None` means that the index was omitted.
+///
+/// It's like Python's slicing. Panics if `step` is 0 (Python raises a `ValueError`).
+///
+/// # Examples
+///
+/// ```
+/// let v = vec![1, 2, 3, 4, 5];
+///
+/// assert_eq!(jabba_lib::jvec::slice(&v, Some(1), Some(-1), 1), [2, 3, 4]);
+/// assert_eq!(jabba_lib::jvec::slice(&v, None, None, -2), [5, 3, 1]);
+/// assert_eq!(jabba_lib::jvec::slice(&v, Some(3), Some(99), 1), [4, 5]);
+/// ```
+///
+/// In Python, it would look like this:
+///
+/// ```python
+/// v = [1, 2, 3, 4, 5]
+///
+/// assert v[1:-1] == [2, 3, 4]
+/// assert v[::-2] == [5, 3, 1]
+/// assert v[3:99] == [4, 5]
+/// ```
+pub fn slice<T: Clone>(v: &[T], start: Option<isize>, stop: Option<isize>, step: isize) -> Vec<T> {
+    slice_indices(v.len(), start, stop, step)
+        .into_iter()
+        .map(|i| v[i].clone())
+        .collect()
+}
+
 // ==========================================================================
 
 #[cfg(test)]
@@ -179,4 +266,44 @@ mod tests {
         assert_eq!(is_palindrome(&[2, 0, 2, 2]), false);
         assert_eq!(is_palindrome(&vec![1, 9, 9, 1]), true);
     }
+
+    #[test]
+    fn slice_indices_test1() {
+        assert_eq!(slice_indices(0, None, None, 1), Vec::<usize>::new());
+        assert_eq!(slice_indices(0, None, None, -1), Vec::<usize>::new());
+        assert_eq!(slice_indices(4, None, None, 1), [0, 1, 2, 3]);
+        assert_eq!(slice_indices(4, None, None, -1), [3, 2, 1, 0]);
+        assert_eq!(slice_indices(4, Some(-1), Some(-5), -1), [3, 2, 1, 0]);
+        assert_eq!(slice_indices(4, Some(99), None, -1), [3, 2, 1, 0]);
+        assert_eq!(slice_indices(4, Some(-99), None, -1), Vec::<usize>::new());
+        assert_eq!(slice_indices(4, Some(99), None, 1), Vec::<usize>::new());
+        assert_eq!(slice_indices(4, Some(2), Some(1), 1), Vec::<usize>::new());
+        assert_eq!(slice_indices(10, Some(1), Some(8), 3), [1, 4, 7]);
+        assert_eq!(slice_indices(10, Some(8), Some(1), -3), [8, 5, 2]);
+        assert_eq!(slice_indices(10, None, None, 100), [0]);
+        // huge steps don't overflow
+        assert_eq!(slice_indices(10, Some(5), None, isize::MAX), [5]);
+        assert_eq!(slice_indices(10, None, None, isize::MAX), [0]);
+        assert_eq!(slice_indices(10, Some(5), None, isize::MIN), [5]);
+        assert_eq!(slice_indices(10, None, None, isize::MIN), [9]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_indices_test2() {
+        slice_indices(4, None, None, 0);
+    }
+
+    #[test]
+    fn slice_test1() {
+        let v = vec!["a", "b", "c", "d"];
+        assert_eq!(slice(&v, None, None, 1), v);
+        assert_eq!(slice(&v, None, None, -1), ["d", "c", "b", "a"]);
+        assert_eq!(slice(&v, Some(-2), None, 1), ["c", "d"]);
+        assert_eq!(slice(&v, None, Some(-2), 1), ["a", "b"]);
+        assert_eq!(slice(&v, Some(-3), Some(-1), 1), ["b", "c"]);
+        assert_eq!(slice(&v, Some(1), None, 2), ["b", "d"]);
+        assert_eq!(slice(&v, Some(-99), Some(99), 1), v);
+        assert_eq!(slice(&[0; 0], Some(-1), Some(1), -1), []);
+    }
 }
@@ -3,6 +3,9 @@
 //! `jabba_lib` is a general-purpose utility library. The API was mainly inspired by
 //! the Python programming language.
 
+// the tests compare against literal bools and use vec![] on purpose
+#![cfg_attr(test, allow(clippy::bool_assert_comparison, clippy::useless_vec))]
+
 pub mod jclipboard;
 pub mod jconsole;
 pub mod jfs;
@@ -12,6 +15,7 @@ pub mod jpermutation;
 pub mod jprocess;
 pub mod jpy;
 pub mod jrandom;
+pub mod jroman;
 pub mod jspell;
 pub mod jstring;
 pub mod jtime;